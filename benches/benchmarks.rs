@@ -0,0 +1,141 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use geo::Point;
+use rusterio::{
+    gdal_engine::{GdalFile, GdalFileBuilder},
+    BlockCache, CrsGeometry, Indexes, Raster, ViewBounds,
+};
+use std::sync::Arc;
+
+const SENTINEL2_FILE_NAME: &str = "S2B_MSIL2A_20241206T093309_N0511_R136_T33PTM_20241206T115919";
+
+fn sentinel2_10m_path() -> String {
+    format!("SENTINEL2_L2A:/vsizip/data/{SENTINEL2_FILE_NAME}.SAFE.zip/{SENTINEL2_FILE_NAME}.SAFE/MTD_MSIL2A.xml:10:EPSG_32633")
+}
+
+fn sentinel2_60m_path() -> String {
+    format!("SENTINEL2_L2A:/vsizip/data/{SENTINEL2_FILE_NAME}.SAFE.zip/{SENTINEL2_FILE_NAME}.SAFE/MTD_MSIL2A.xml:60:EPSG_32633")
+}
+
+/// Read a 512x512 window of the first 10m band,
+/// offset so it straddles native blocks.
+fn read_window(file: GdalFile<u16>) {
+    Raster::from_file(file, Indexes::from([0usize]))
+        .unwrap()
+        .view(None, Indexes::all())
+        .unwrap()
+        .clip(ViewBounds::new((100, 100), (512, 512)))
+        .unwrap()
+        .read()
+        .unwrap();
+}
+
+fn block_aligned_reads(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read 512x512 window");
+    group.sample_size(10);
+    for block_aligned in [true, false] {
+        let name = if block_aligned {
+            "aligned"
+        } else {
+            "unaligned"
+        };
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let file = GdalFileBuilder::new(sentinel2_10m_path())
+                    .block_aligned_reads(block_aligned)
+                    .open()
+                    .unwrap();
+                read_window(file)
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Read 256x256 tiles overlapping by 32 pixels
+/// over a 1024x1024 window of the first 10m band.
+fn read_overlapping_tiles(file: GdalFile<u16>) {
+    let view = Raster::from_file(file, Indexes::from([0usize]))
+        .unwrap()
+        .view(None, Indexes::all())
+        .unwrap()
+        .clip(ViewBounds::new((0, 0), (1024, 1024)))
+        .unwrap();
+    for tile in view.tiles((256, 256), 32).unwrap() {
+        tile.read().unwrap();
+    }
+}
+
+fn block_cache(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read overlapping tiles");
+    group.sample_size(10);
+    group.bench_function("uncached", |b| {
+        b.iter(|| {
+            read_overlapping_tiles(GdalFileBuilder::new(sentinel2_10m_path()).open().unwrap())
+        })
+    });
+    // Shared across iterations, so later ones read cached blocks.
+    let cache = Arc::new(BlockCache::new(256 << 20));
+    group.bench_function("cached", |b| {
+        b.iter(|| {
+            let file = GdalFileBuilder::new(sentinel2_10m_path())
+                .block_cache(Arc::clone(&cache))
+                .open()
+                .unwrap();
+            read_overlapping_tiles(file)
+        })
+    });
+    group.finish();
+}
+
+/// Build a raster of all bands of the 60m group,
+/// the one holding most Sentinel-2 bands.
+fn parallel_bands(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build raster bands");
+    group.sample_size(10);
+    for parallel in [true, false] {
+        let name = if parallel { "parallel" } else { "serial" };
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let file = GdalFileBuilder::<u16>::new(sentinel2_60m_path())
+                    .parallel_bands(parallel)
+                    .open()
+                    .unwrap();
+                Raster::from_file(file, Indexes::all()).unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Sample 100 points along the diagonal of the first 10m band,
+/// one single pixel read each.
+fn repeated_small_reads(c: &mut Criterion) {
+    let raster =
+        Raster::<u16>::new::<GdalFile<u16>>(sentinel2_10m_path(), Indexes::from([0usize])).unwrap();
+    let (min, max) = (raster.bounds().min(), raster.bounds().max());
+    let points: Vec<CrsGeometry<Point>> = (0..100)
+        .map(|idx| {
+            let fraction = (idx as f64 + 0.5) / 100.;
+            let point = Point::new(
+                min.x + fraction * (max.x - min.x),
+                max.y - fraction * (max.y - min.y),
+            );
+            CrsGeometry::new(raster.crs(), point)
+        })
+        .collect();
+    let mut group = c.benchmark_group("repeated small reads");
+    group.sample_size(10);
+    group.bench_function("sample 100 points", |b| {
+        b.iter(|| raster.sample(&points, Indexes::all()).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    block_aligned_reads,
+    block_cache,
+    parallel_bands,
+    repeated_small_reads
+);
+criterion_main!(benches);
@@ -1,6 +1,12 @@
-use std::marker::PhantomData;
+use std::{
+    marker::PhantomData,
+    ops::{Index, Range},
+};
 
-use crate::components::DataType;
+use crate::{
+    components::{band::RadiometricInfo, DataType},
+    errors::{Result, RusterioError},
+};
 
 #[derive(Debug)]
 pub struct Buffer<T, const ND: usize> {
@@ -27,11 +33,273 @@ impl<T: DataType, const ND: usize> Buffer<T, ND> {
     }
 }
 
+/// How channel values are scaled into `u8`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stretch {
+    /// Linearly between the channel min and max.
+    MinMax,
+    /// Linearly between the `low` and `high` percentiles (in `[0, 1]`)
+    /// of the channel, clamping values outside.
+    Percentile(f64, f64),
+}
+
+impl<T: DataType + num::ToPrimitive> Buffer<T, 3> {
+    /// Scale each channel into `u8` with `stretch`.
+    ///
+    /// Pixels equal to the `nodata` of their channel are left out
+    /// of the scaling and set to zero.
+    pub(crate) fn stretch(&self, stretch: Stretch, nodata: &[Option<f64>]) -> Buffer<u8, 3> {
+        let (low, high) = match stretch {
+            Stretch::MinMax => (0., 1.),
+            Stretch::Percentile(low, high) => (low, high),
+        };
+        let mut stretched = Buffer::new(self.shape);
+        let channel_len = self.shape[1] * self.shape[2];
+        if channel_len == 0 {
+            return stretched;
+        }
+        self.data
+            .chunks(channel_len)
+            .zip(stretched.as_mut().chunks_mut(channel_len))
+            .zip(nodata.iter().chain(std::iter::repeat(&None)))
+            .for_each(|((channel, stretched_channel), nodata)| {
+                let nodata_val = nodata.and_then(num::cast::<f64, T>);
+                let mut sorted: Vec<f64> = channel
+                    .iter()
+                    .filter(|val| Some(**val) != nodata_val)
+                    .map(|val| val.to_f64().unwrap_or_default())
+                    .collect();
+                if sorted.is_empty() {
+                    return;
+                }
+                sorted.sort_by(f64::total_cmp);
+                let percentile = |p: f64| sorted[((sorted.len() - 1) as f64 * p).round() as usize];
+                let (min, max) = (percentile(low), percentile(high));
+                let range = (max - min).max(f64::EPSILON);
+                stretched_channel
+                    .iter_mut()
+                    .zip(channel)
+                    .filter(|(_, val)| Some(**val) != nodata_val)
+                    .for_each(|(stretched_val, val)| {
+                        let val = val.to_f64().unwrap_or_default();
+                        *stretched_val = (((val - min) / range).clamp(0., 1.) * 255.).round() as u8;
+                    });
+            });
+        stretched
+    }
+}
+
+impl<T: DataType> Buffer<T, 3> {
+    /// Apply per channel `calibration`, `output = input * gain + offset`.
+    pub fn apply_gain_offset(&self, calibration: &[RadiometricInfo]) -> Result<Buffer<f64, 3>> {
+        if calibration.len() != self.channels() {
+            return Err(RusterioError::BandCountMismatch {
+                expected: self.channels(),
+                found: calibration.len(),
+            });
+        }
+        let channel_len = self.height() * self.width();
+        let data = calibration
+            .iter()
+            .enumerate()
+            .flat_map(|(channel_idx, RadiometricInfo { gain, offset })| {
+                self.data[channel_idx * channel_len..(channel_idx + 1) * channel_len]
+                    .iter()
+                    .map(move |val| val.to_f64().unwrap_or_default() * gain + offset)
+            })
+            .collect();
+        Ok(Buffer::from_owned_parts(data, self.shape))
+    }
+}
+
+#[cfg(feature = "pca")]
+impl<T: DataType> Buffer<T, 3> {
+    /// Principal component analysis across channels.
+    ///
+    /// Returns the data projected onto the first `n_components`
+    /// principal components (C = `n_components`, H, W),
+    /// and the ratio of variance each component explains,
+    /// all zero if the channels are constant.
+    pub fn pca(&self, n_components: usize) -> Result<(Buffer<f64, 3>, Vec<f64>)> {
+        use nalgebra::{DMatrix, SymmetricEigen};
+
+        let (channels, height, width) = self.chw();
+        if n_components == 0 || n_components > channels {
+            return Err(RusterioError::InvalidComponentCount {
+                requested: n_components,
+                channels,
+            });
+        }
+        let num_pixels = height * width;
+        // (C, H * W) with centered channels.
+        let mut centered = DMatrix::from_row_iterator(
+            channels,
+            num_pixels,
+            self.data.iter().map(|val| val.to_f64().unwrap_or_default()),
+        );
+        centered
+            .row_iter_mut()
+            .for_each(|mut channel| channel.add_scalar_mut(-channel.mean()));
+        let covariance =
+            &centered * centered.transpose() / (num_pixels.saturating_sub(1).max(1) as f64);
+
+        let eigen = SymmetricEigen::new(covariance);
+        let mut order: Vec<usize> = (0..channels).collect();
+        order.sort_by(|&lhs, &rhs| eigen.eigenvalues[rhs].total_cmp(&eigen.eigenvalues[lhs]));
+        let total_variance: f64 = eigen.eigenvalues.iter().sum();
+        let explained_variance_ratio = order
+            .iter()
+            .take(n_components)
+            .map(|&idx| {
+                if total_variance > 0. {
+                    eigen.eigenvalues[idx] / total_variance
+                } else {
+                    0.
+                }
+            })
+            .collect();
+
+        let components = DMatrix::from_fn(n_components, channels, |component, channel| {
+            eigen.eigenvectors[(channel, order[component])]
+        });
+        let projected = components * centered;
+        let data = projected.transpose().as_slice().to_vec();
+        Ok((
+            Buffer::from_owned_parts(data, [n_components, height, width]),
+            explained_variance_ratio,
+        ))
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl<T> Buffer<T, 3> {
+    /// Move data into an array of shape (C, H, W), without copying.
+    pub fn into_ndarray(self) -> ndarray::Array3<T> {
+        let [channels, height, width] = self.shape;
+        ndarray::Array3::from_shape_vec((channels, height, width), self.data)
+            .expect("buffer length matches its shape")
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl<T> Buffer<T, 1> {
+    /// Move data into an array, without copying.
+    pub fn into_ndarray(self) -> ndarray::Array1<T> {
+        ndarray::Array1::from_vec(self.data)
+    }
+}
+
+/// Pixel value type with a NumPy dtype.
+#[cfg(feature = "npy")]
+pub trait NpyDataType: Copy {
+    /// NumPy array-protocol type string, e.g. `"<u2"`.
+    const DESCR: &'static str;
+    /// Write little endian bytes of `data`, in order, to `writer`.
+    fn write_le_bytes(data: &[Self], writer: &mut impl std::io::Write) -> std::io::Result<()>;
+}
+
+#[cfg(feature = "npy")]
+macro_rules! impl_npy_data_type {
+    ($($data_type:ty => $descr:literal),*) => {
+        $(impl NpyDataType for $data_type {
+            const DESCR: &'static str = $descr;
+            fn write_le_bytes(
+                data: &[Self],
+                writer: &mut impl std::io::Write,
+            ) -> std::io::Result<()> {
+                data.iter()
+                    .try_for_each(|val| writer.write_all(&val.to_le_bytes()))
+            }
+        })*
+    };
+}
+
+#[cfg(feature = "npy")]
+impl_npy_data_type!(
+    u8 => "|u1", i8 => "|i1", u16 => "<u2", i16 => "<i2",
+    u32 => "<u4", i32 => "<i4", f32 => "<f4", f64 => "<f8"
+);
+
+#[cfg(feature = "npy")]
+impl NpyDataType for bool {
+    const DESCR: &'static str = "|b1";
+    fn write_le_bytes(data: &[Self], writer: &mut impl std::io::Write) -> std::io::Result<()> {
+        data.iter()
+            .try_for_each(|&val| writer.write_all(&[val as u8]))
+    }
+}
+
+#[cfg(feature = "npy")]
+impl<T: NpyDataType, const ND: usize> Buffer<T, ND> {
+    /// Write as a NumPy `.npy` file (format version 1.0) at `path`,
+    /// keeping the row-major shape, e.g. (C, H, W).
+    pub fn write_npy(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        use std::io::Write;
+
+        let shape = match self.shape.as_slice() {
+            [len] => format!("({len},)"),
+            shape => format!(
+                "({})",
+                shape
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+        let mut header = format!(
+            "{{'descr': '{}', 'fortran_order': False, 'shape': {shape}, }}",
+            T::DESCR
+        );
+        // Magic string, version and header length take 10 bytes,
+        // data starts 64 byte aligned after the newline ending the header.
+        let padded_len = (10 + header.len() + 1).div_ceil(64) * 64 - 10;
+        header.extend(std::iter::repeat_n(' ', padded_len - header.len() - 1));
+        header.push('\n');
+
+        let path = path.as_ref();
+        let write = || {
+            let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+            file.write_all(b"\x93NUMPY\x01\x00")?;
+            file.write_all(&(header.len() as u16).to_le_bytes())?;
+            file.write_all(header.as_bytes())?;
+            // Per value writes go through the buffer, without copying all data.
+            T::write_le_bytes(&self.data, &mut file)?;
+            file.flush()
+        };
+        write().map_err(|source| RusterioError::WriteError {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
 impl<T, const ND: usize> Buffer<T, ND> {
+    /// Build buffer from row-major `data` and its `shape`.
+    ///
+    /// Panics if `data` length doesn't match `shape`.
+    pub fn from_owned_parts(data: Vec<T>, shape: [usize; ND]) -> Self {
+        assert_eq!(
+            data.len(),
+            shape.iter().product::<usize>(),
+            "data length doesn't match buffer shape {shape:?}"
+        );
+        Self {
+            data,
+            shape,
+            _t: PhantomData,
+        }
+    }
+
     pub fn as_ref(&self) -> &[T] {
         &self.data
     }
 
+    /// Row-major data.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
     pub fn as_mut(&mut self) -> &mut [T] {
         &mut self.data
     }
@@ -44,3 +312,305 @@ impl<T, const ND: usize> Buffer<T, ND> {
         self.shape
     }
 }
+
+impl<T> Buffer<T, 3> {
+    /// Number of channels (C).
+    pub fn channels(&self) -> usize {
+        self.shape[0]
+    }
+
+    /// Number of rows (H).
+    pub fn height(&self) -> usize {
+        self.shape[1]
+    }
+
+    /// Number of columns (W).
+    pub fn width(&self) -> usize {
+        self.shape[2]
+    }
+
+    /// Shape as (C, H, W).
+    pub fn chw(&self) -> (usize, usize, usize) {
+        (self.channels(), self.height(), self.width())
+    }
+}
+
+impl<T: Copy> Buffer<T, 3> {
+    /// Copy of the (C, `h_range`, `w_range`) sub-region.
+    ///
+    /// Fails with [RusterioError::ReadBoundsOutOfRange]
+    /// if the ranges don't fit in (H, W).
+    pub fn window(&self, h_range: Range<usize>, w_range: Range<usize>) -> Result<Buffer<T, 3>> {
+        let (channels, height, width) = self.chw();
+        if h_range.start > h_range.end
+            || w_range.start > w_range.end
+            || h_range.end > height
+            || w_range.end > width
+        {
+            return Err(RusterioError::ReadBoundsOutOfRange {
+                offset: (w_range.start, h_range.start),
+                shape: (
+                    w_range.end.saturating_sub(w_range.start),
+                    h_range.end.saturating_sub(h_range.start),
+                ),
+                raster_size: (width, height),
+            });
+        }
+        let shape = [channels, h_range.len(), w_range.len()];
+        let data = (0..channels)
+            .flat_map(|channel| {
+                h_range
+                    .clone()
+                    .map(move |row| (channel * height + row) * width)
+            })
+            .flat_map(|row_start| {
+                self.data[row_start + w_range.start..row_start + w_range.end]
+                    .iter()
+                    .copied()
+            })
+            .collect();
+        Ok(Buffer::from_owned_parts(data, shape))
+    }
+
+    /// Rearrange (C, H, W) data into a contiguous (H, W, C) buffer.
+    pub fn transpose_to_hwc(&self) -> Buffer<T, 3> {
+        let [channels, height, width] = self.shape;
+        let data = transpose(&self.data, channels, height * width);
+        Buffer::from_owned_parts(data, [height, width, channels])
+    }
+
+    /// Rearrange (H, W, C) data into a contiguous (C, H, W) buffer.
+    pub fn transpose_to_chw(&self) -> Buffer<T, 3> {
+        let [height, width, channels] = self.shape;
+        let data = transpose(&self.data, height * width, channels);
+        Buffer::from_owned_parts(data, [channels, height, width])
+    }
+
+    /// Channel last (H, W, C) buffer from a (C, H, W) one,
+    /// e.g. for image crates, see [Buffer::transpose_to_hwc].
+    pub fn to_channel_last(self) -> Buffer<T, 3> {
+        self.transpose_to_hwc()
+    }
+
+    /// Channel first (C, H, W) buffer from a (H, W, C) one,
+    /// inverse of [Buffer::to_channel_last].
+    pub fn to_channel_first(self) -> Buffer<T, 3> {
+        self.transpose_to_chw()
+    }
+
+    /// Data in (H, W, C) order, channels interleaved per pixel.
+    pub fn to_flat_vec_hwc(&self) -> Vec<T> {
+        self.transpose_to_hwc().data
+    }
+
+    /// Data in (C, H, W) order, the buffer layout.
+    pub fn to_flat_vec_chw(&self) -> Vec<T> {
+        self.data.clone()
+    }
+}
+
+/// Side of the square tiles [transpose] copies at a time.
+const TRANSPOSE_TILE: usize = 32;
+
+/// Transpose row-major `rows` x `cols` `data`,
+/// tile by tile so reads and writes stay within cache.
+fn transpose<T: Copy>(data: &[T], rows: usize, cols: usize) -> Vec<T> {
+    let Some(&first) = data.first() else {
+        return Vec::new();
+    };
+    let mut transposed = vec![first; rows * cols];
+    for row_start in (0..rows).step_by(TRANSPOSE_TILE) {
+        for col_start in (0..cols).step_by(TRANSPOSE_TILE) {
+            for row in row_start..(row_start + TRANSPOSE_TILE).min(rows) {
+                for col in col_start..(col_start + TRANSPOSE_TILE).min(cols) {
+                    transposed[col * rows + row] = data[row * cols + col];
+                }
+            }
+        }
+    }
+    transposed
+}
+
+/// Element at N-d `index`, e.g. `buff[[c, h, w]]` for a (C, H, W) buffer.
+impl<T, const ND: usize> Index<[usize; ND]> for Buffer<T, ND> {
+    type Output = T;
+    fn index(&self, index: [usize; ND]) -> &T {
+        debug_assert!(
+            index.iter().zip(self.shape).all(|(idx, len)| *idx < len),
+            "index {index:?} out of range for buffer of shape {:?}",
+            self.shape
+        );
+        let offset = index
+            .iter()
+            .zip(self.shape)
+            .fold(0, |offset, (idx, len)| offset * len + idx);
+        &self.data[offset]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentile_stretch() {
+        let buff = Buffer::<u16, 3>::from_owned_parts((0..100).collect(), [1, 10, 10]);
+        let stretched = buff.stretch(Stretch::Percentile(0.02, 0.98), &[None]);
+        // 2% and 98% of 0..100 fall on 2 and 97.
+        assert_eq!(&stretched.as_ref()[..4], &[0, 0, 0, 3]);
+        assert_eq!(&stretched.as_ref()[96..], &[252, 255, 255, 255]);
+        let stretched = buff.stretch(Stretch::MinMax, &[Some(0.)]);
+        assert_eq!(&stretched.as_ref()[..3], &[0, 0, 3]);
+        assert_eq!(stretched.as_ref()[99], 255);
+    }
+
+    #[cfg(feature = "npy")]
+    #[test]
+    fn write_npy() {
+        let buff = Buffer::<u16, 3>::from_owned_parts((0..24).collect(), [2, 3, 4]);
+        let path = std::env::temp_dir().join("rusterio_write_npy.npy");
+        buff.write_npy(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+        let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        assert_eq!((10 + header_len) % 64, 0);
+        let header = std::str::from_utf8(&bytes[10..10 + header_len]).unwrap();
+        assert!(header.contains("'descr': '<u2'"));
+        assert!(header.contains("'fortran_order': False"));
+        assert!(header.contains("'shape': (2, 3, 4)"));
+        assert!(header.ends_with('\n'));
+        assert_eq!(bytes.len(), 10 + header_len + 24 * 2);
+        assert_eq!(&bytes[10 + header_len + 2..10 + header_len + 4], &[1, 0]);
+        let missing_dir = std::env::temp_dir().join("rusterio_missing_dir/out.npy");
+        assert!(matches!(
+            buff.write_npy(&missing_dir),
+            Err(RusterioError::WriteError { path, .. }) if path == missing_dir
+        ));
+    }
+
+    #[test]
+    fn apply_gain_offset() {
+        let buff = Buffer::<u16, 3>::from_owned_parts(vec![1, 2, 3, 4], [2, 1, 2]);
+        let calibration = [
+            RadiometricInfo {
+                gain: 2.,
+                offset: 0.,
+            },
+            RadiometricInfo {
+                gain: 0.5,
+                offset: -1.,
+            },
+        ];
+        let calibrated = buff.apply_gain_offset(&calibration).unwrap();
+        assert_eq!(calibrated.as_ref(), &[2., 4., 0.5, 1.]);
+        assert!(matches!(
+            buff.apply_gain_offset(&calibration[..1]),
+            Err(RusterioError::BandCountMismatch {
+                expected: 2,
+                found: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn transpose() {
+        // 2 channels of 2x3
+        let chw = Buffer::from_owned_parts((0..12).collect::<Vec<u16>>(), [2, 2, 3]);
+        let hwc = chw.transpose_to_hwc();
+        assert_eq!(hwc.shape(), [2, 3, 2]);
+        assert_eq!(hwc.as_ref(), &[0, 6, 1, 7, 2, 8, 3, 9, 4, 10, 5, 11]);
+        let round_trip = hwc.transpose_to_chw();
+        assert_eq!(round_trip.shape(), chw.shape());
+        assert_eq!(round_trip.as_ref(), chw.as_ref());
+    }
+
+    #[test]
+    fn channel_last_round_trip() {
+        // Spans several transpose tiles.
+        let shape = [3, 40, 35];
+        let data: Vec<u32> = (0..shape.iter().product::<usize>() as u32).collect();
+        let chw = Buffer::from_owned_parts(data.clone(), shape);
+        let hwc = chw.to_channel_last();
+        assert_eq!(hwc.shape(), [40, 35, 3]);
+        assert_eq!(hwc[[39, 2, 1]], data[(40 + 39) * 35 + 2]);
+        let round_trip = hwc.to_channel_first();
+        assert_eq!(round_trip.shape(), shape);
+        assert_eq!(round_trip.as_ref(), data.as_slice());
+    }
+
+    #[test]
+    fn window() {
+        // 2 channels of 3x3
+        let buff = Buffer::from_owned_parts((0..18).collect::<Vec<u16>>(), [2, 3, 3]);
+        let window = buff.window(1..3, 0..2).unwrap();
+        assert_eq!(window.shape(), [2, 2, 2]);
+        assert_eq!(window.as_ref(), &[3, 4, 6, 7, 12, 13, 15, 16]);
+        assert!(matches!(
+            buff.window(2..4, 0..1),
+            Err(RusterioError::ReadBoundsOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn to_flat_vec() {
+        // 3 channels (R, G, B) of 1x2
+        let buff = Buffer::from_owned_parts(vec![1u8, 2, 10, 20, 100, 200], [3, 1, 2]);
+        assert_eq!(buff.to_flat_vec_hwc(), vec![1, 10, 100, 2, 20, 200]);
+        assert_eq!(buff.to_flat_vec_chw(), vec![1, 2, 10, 20, 100, 200]);
+    }
+
+    #[cfg(feature = "pca")]
+    #[test]
+    fn pca() {
+        // Second channel is twice the first, so one component explains all variance.
+        let buff = Buffer::from_owned_parts(vec![1u16, 2, 3, 4, 2, 4, 6, 8], [2, 2, 2]);
+        let (projected, explained_variance_ratio) = buff.pca(1).unwrap();
+        assert_eq!(projected.shape(), [1, 2, 2]);
+        assert!((explained_variance_ratio[0] - 1.).abs() < 1e-9);
+        let projected = projected.as_ref();
+        let step = projected[1] - projected[0];
+        assert!((step.abs() - 5f64.sqrt()).abs() < 1e-9);
+        assert!(projected
+            .windows(2)
+            .all(|pair| (pair[1] - pair[0] - step).abs() < 1e-9));
+        assert!(matches!(
+            buff.pca(3),
+            Err(RusterioError::InvalidComponentCount { .. })
+        ));
+    }
+
+    #[cfg(feature = "pca")]
+    #[test]
+    fn pca_of_constant_channels() {
+        let buff = Buffer::from_owned_parts(vec![7u16; 8], [2, 2, 2]);
+        let (projected, explained_variance_ratio) = buff.pca(2).unwrap();
+        assert_eq!(explained_variance_ratio, [0., 0.]);
+        assert!(projected.as_ref().iter().all(|val| *val == 0.));
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn into_ndarray() {
+        let buff = Buffer::from_owned_parts((0..12).collect::<Vec<u16>>(), [2, 2, 3]);
+        let arr = buff.into_ndarray();
+        assert_eq!(arr.dim(), (2, 2, 3));
+        assert_eq!(arr[[1, 0, 2]], 8);
+    }
+
+    #[test]
+    fn index() {
+        let buff = Buffer::from_owned_parts((0..12).collect::<Vec<u16>>(), [2, 2, 3]);
+        assert_eq!(buff[[0, 0, 0]], 0);
+        assert_eq!(buff[[1, 0, 2]], 8);
+        assert_eq!(buff[[1, 1, 2]], 11);
+        assert_eq!(buff.as_slice(), buff.as_ref());
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn index_out_of_range() {
+        let buff = Buffer::from_owned_parts((0..12).collect::<Vec<u16>>(), [2, 2, 3]);
+        let _ = buff[[0, 2, 0]];
+    }
+}
@@ -1,11 +1,24 @@
 use geo::Coord;
+use std::{hash::Hasher, rc::Rc};
 
 use crate::{
-    components::{bounds::ReadBounds, DataType, Metadata},
-    errors::Result,
-    Buffer,
+    components::{
+        bounds::{Bounds, ReadBounds},
+        resampling::{resample_nearest, resample_window, GdalResamplingAlgorithm},
+        DataType, Metadata,
+    },
+    errors::{Result, RusterioError},
+    Buffer, CoordUtils,
 };
 
+/// Linear radiometric calibration of a band,
+/// `value = raw * gain + offset`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RadiometricInfo {
+    pub gain: f64,
+    pub offset: f64,
+}
+
 /// Trait for accessing name,
 /// description and metadata of
 /// a raster band.
@@ -13,11 +26,166 @@ pub trait BandInfo: std::fmt::Debug {
     fn name(&self) -> String;
     fn description(&self) -> Result<String>;
     fn metadata(&self) -> Result<Metadata>;
+    /// Value marking pixels with no data, if any.
+    fn nodata(&self) -> Result<Option<f64>>;
+    /// Native (Width, Height) of the band, if known without a [BandReader].
+    fn size(&self) -> Result<Option<(usize, usize)>> {
+        Ok(None)
+    }
+    /// Native (Width, Height) block size the band is stored in, if any.
+    fn block_size(&self) -> Result<Option<(usize, usize)>> {
+        Ok(None)
+    }
+    /// Number of reduced resolution overviews.
+    fn overview_count(&self) -> Result<usize> {
+        Ok(0)
+    }
+    /// (Width, Height) of overview at `level`, from 0 (finest).
+    fn overview_size(&self, level: usize) -> Result<(usize, usize)> {
+        Err(RusterioError::OverviewNotFound(level))
+    }
+}
+
+/// [BandInfo] with name overridden.
+#[derive(Debug)]
+pub(crate) struct NamedBandInfo {
+    pub name: String,
+    pub info: Rc<dyn BandInfo>,
+}
+
+impl BandInfo for NamedBandInfo {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+    fn description(&self) -> Result<String> {
+        self.info.description()
+    }
+    fn metadata(&self) -> Result<Metadata> {
+        self.info.metadata()
+    }
+    fn nodata(&self) -> Result<Option<f64>> {
+        self.info.nodata()
+    }
+    fn size(&self) -> Result<Option<(usize, usize)>> {
+        self.info.size()
+    }
+    fn block_size(&self) -> Result<Option<(usize, usize)>> {
+        self.info.block_size()
+    }
+    fn overview_count(&self) -> Result<usize> {
+        self.info.overview_count()
+    }
+    fn overview_size(&self, level: usize) -> Result<(usize, usize)> {
+        self.info.overview_size(level)
+    }
 }
 
 /// Trait for I/O on a raster band.
 pub trait BandReader<T: DataType>: Send + Sync + std::fmt::Debug {
+    /// Native (Width, Height) of the band.
+    fn raster_size(&self) -> Result<(usize, usize)>;
     fn read_into_slice(&self, bounds: &ReadBounds, slice: &mut [T]) -> Result<()>;
     fn read_to_buffer(&self, bounds: &ReadBounds) -> Result<Buffer<T, 1>>; // TODO: add default impl
     fn read_pixel(&self, offset: Coord<usize>) -> Result<T>;
+    /// Read `bounds` resampled into `slice` of `out_shape` (Width, Height)
+    /// with `algorithm`, where the engine supports it.
+    ///
+    /// Defaults to reading at native resolution and resampling with
+    /// nearest neighbour of the top left source pixel, or else
+    /// with the closest [ResamplingMethod](crate::ResamplingMethod).
+    fn read_into_slice_resampled(
+        &self,
+        bounds: &ReadBounds,
+        out_shape: (usize, usize),
+        slice: &mut [T],
+        algorithm: GdalResamplingAlgorithm,
+    ) -> Result<()> {
+        let native_buff = self.read_to_buffer(bounds)?;
+        let shape = bounds.shape();
+        if algorithm == GdalResamplingAlgorithm::NearestNeighbour {
+            resample_nearest(native_buff.as_ref(), shape.x_y(), out_shape, slice);
+            return Ok(());
+        }
+        resample_window(
+            native_buff.as_ref(),
+            shape.x_y(),
+            (Coord::zero(), shape.try_cast()?),
+            out_shape,
+            slice,
+            algorithm.fallback_method(),
+        );
+        Ok(())
+    }
+    /// Read `bounds`, in pixels of overview `level`, into `slice`.
+    ///
+    /// See [BandInfo::overview_count].
+    fn read_overview_into_slice(
+        &self,
+        level: usize,
+        _bounds: &ReadBounds,
+        _slice: &mut [T],
+    ) -> Result<()> {
+        Err(RusterioError::OverviewNotFound(level))
+    }
+    /// Whether [BandReader::read_into_slice_resampled]
+    /// resamples in the engine rather than with the default fallback.
+    fn resamples_natively(&self) -> bool {
+        false
+    }
+    /// Feed what identifies the band source into `state`,
+    /// such that a changed source hashes differently.
+    fn hash_source(&self, state: &mut dyn Hasher);
+    /// Like [BandReader::read_into_slice],
+    /// but zeroes pixels where `mask` is `true`.
+    ///
+    /// `mask` shape must be (Height, Width) of `bounds`.
+    fn read_into_slice_masked(
+        &self,
+        bounds: &ReadBounds,
+        slice: &mut [T],
+        mask: &Buffer<bool, 2>,
+    ) -> Result<()> {
+        let read_shape = bounds.shape();
+        let mask_shape = [read_shape.y, read_shape.x];
+        if mask.shape() != mask_shape {
+            return Err(RusterioError::ShapeMismatch {
+                expected: mask_shape.to_vec(),
+                found: mask.shape().to_vec(),
+            });
+        }
+        self.read_into_slice(bounds, slice)?;
+        slice
+            .iter_mut()
+            .zip(mask.as_ref())
+            .filter(|(_, masked)| **masked)
+            .for_each(|(val, _)| *val = T::zero());
+        Ok(())
+    }
+    /// Read `bounds` into `out`, of shape (Height, Width) of `bounds`.
+    ///
+    /// Reads straight into `out` if it is contiguous in standard layout,
+    /// otherwise through a buffer copied into it.
+    #[cfg(feature = "ndarray")]
+    fn read_into_ndarray(
+        &self,
+        bounds: &ReadBounds,
+        mut out: ndarray::ArrayViewMut2<T>,
+    ) -> Result<()> {
+        let read_shape = bounds.shape();
+        let expected_shape = [read_shape.y, read_shape.x];
+        if out.shape() != expected_shape {
+            return Err(RusterioError::ShapeMismatch {
+                expected: expected_shape.to_vec(),
+                found: out.shape().to_vec(),
+            });
+        }
+        if let Some(slice) = out.as_slice_mut() {
+            return self.read_into_slice(bounds, slice);
+        }
+        let buff = self.read_to_buffer(bounds)?;
+        out.iter_mut()
+            .zip(buff.as_ref())
+            .for_each(|(val, read_val)| *val = *read_val);
+        Ok(())
+    }
 }
@@ -6,13 +6,16 @@ use crate::{
         ambassador_impl_Area, ambassador_impl_GeometryTrait, ambassador_impl_MapCoords,
         ambassador_impl_RectTrait,
     },
-    components::transforms::{GeoReadTransform, ViewReadTransform},
-    errors::Result,
-    intersection::Intersection,
-    CoordUtils, CrsGeometry, LineUtils,
+    components::transforms::{GeoReadTransform, ReadGeoTransform, ViewReadTransform},
+    errors::{Result, RusterioError},
+    intersection::{BoundingUnion, Intersection},
+    CoordUtils, Crs, CrsGeometry, LineUtils,
 };
-use geo::{AffineOps, Area, BoundingRect, Coord, CoordNum, Line, MapCoords, Rect};
+use gdal::spatial_ref::SpatialRef;
+use geo::{AffineOps, Area, BoundingRect, Coord, CoordNum, Geometry, Line, MapCoords, Rect};
 use geo_traits::GeometryTrait;
+use std::fmt::{self, Display};
+use wkt::TryFromWkt;
 
 /// Trait for shared Bound implementations.
 ///
@@ -76,6 +79,13 @@ impl Intersection for GeoBounds {
     }
 }
 
+impl BoundingUnion for GeoBounds {
+    type Output = GeoBounds;
+    fn bounding_union(&self, rhs: &Self) -> Result<Self::Output> {
+        Ok(GeoBounds(self.0.bounding_union(&rhs.0)?))
+    }
+}
+
 impl Bounds for GeoBounds {}
 
 impl From<CrsGeometry<Rect>> for GeoBounds {
@@ -84,6 +94,21 @@ impl From<CrsGeometry<Rect>> for GeoBounds {
     }
 }
 
+impl Display for GeoBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (min, max) = (self.min(), self.max());
+        write!(
+            f,
+            "GeoBounds(min=({}, {}), max=({}, {}), crs={})",
+            min.x,
+            min.y,
+            max.x,
+            max.y,
+            self.crs()
+        )
+    }
+}
+
 impl From<&GeoBounds> for Line {
     fn from(value: &GeoBounds) -> Self {
         Line::new(value.min(), value.max())
@@ -95,6 +120,136 @@ impl GeoBounds {
         self.min()
     }
 
+    /// Crs of the bounds, as reported by the file,
+    /// e.g. a WKT definition or `"EPSG:32633"`.
+    pub fn crs(&self) -> &str {
+        self.0.crs()
+    }
+
+    /// Bounds from `min` and `max` (Longitude, Latitude) in degrees,
+    /// in `EPSG:4326`.
+    pub fn from_lonlat(min: (f64, f64), max: (f64, f64)) -> GeoBounds {
+        GeoBounds(CrsGeometry::new(Crs::new("EPSG:4326"), Rect::new(min, max)))
+    }
+
+    /// Bounds expanded by `amount` crs units on every side.
+    ///
+    /// A negative `amount` shrinks them, at most to their center.
+    pub fn pad(&self, amount: f64) -> GeoBounds {
+        let (min, max) = (self.min(), self.max());
+        let center = (min + max) / 2.;
+        let pad = Coord {
+            x: amount.max(-self.width() / 2.),
+            y: amount.max(-self.height() / 2.),
+        };
+        let (min, max) = (min - pad, max + pad);
+        // Guard against rounding past the center.
+        let rect = Rect::new(
+            min.operate(&center, f64::min),
+            max.operate(&center, f64::max),
+        );
+        GeoBounds(CrsGeometry::new(Crs::new(self.crs()), rect))
+    }
+
+    /// GDAL ordered `[xoff, a, b, yoff, d, e]` geo transform
+    /// of a north up raster of `raster_size` (Width, Height) covering the bounds.
+    pub fn geo_transform(&self, raster_size: (usize, usize)) -> [f64; 6] {
+        let pixel_size = self.pixel_size(raster_size);
+        [
+            self.min().x,
+            pixel_size.x,
+            0.,
+            self.max().y,
+            0.,
+            -pixel_size.y,
+        ]
+    }
+
+    /// Pixel (Width, Height) of a north up raster
+    /// of `raster_size` (Width, Height) covering the bounds.
+    pub fn pixel_size(&self, raster_size: (usize, usize)) -> Coord<f64> {
+        Coord {
+            x: self.width() / raster_size.0 as f64,
+            y: self.height() / raster_size.1 as f64,
+        }
+    }
+
+    /// Fractional (column, row) of `coord` in a north up raster
+    /// of `raster_size` (Width, Height) covering the bounds.
+    pub fn pixel_position(&self, raster_size: (usize, usize), coord: Coord<f64>) -> Coord<f64> {
+        let pixel_size = self.pixel_size(raster_size);
+        Coord {
+            x: (coord.x - self.min().x) / pixel_size.x,
+            y: (self.max().y - coord.y) / pixel_size.y,
+        }
+    }
+
+    /// Crs coord of the fractional (column, row) `pixel` of a north up raster
+    /// of `raster_size` (Width, Height) covering the bounds.
+    pub fn pixel_coord(&self, raster_size: (usize, usize), pixel: Coord<f64>) -> Coord<f64> {
+        let pixel_size = self.pixel_size(raster_size);
+        Coord {
+            x: self.min().x + pixel.x * pixel_size.x,
+            y: self.max().y - pixel.y * pixel_size.y,
+        }
+    }
+
+    /// Transform from pixels of a north up raster
+    /// of `raster_size` (Width, Height) covering the bounds.
+    pub fn read_geo_transform(&self, raster_size: (usize, usize)) -> ReadGeoTransform {
+        ReadGeoTransform::from_gdal(self.geo_transform(raster_size), self.interned_crs().clone())
+    }
+
+    /// Bounds reprojected to `crs`, see [CrsGeometry::projected_rect].
+    pub fn with_crs(self, crs: &str) -> Result<GeoBounds> {
+        let rect = self.0.projected_rect(crs)?;
+        Ok(GeoBounds(CrsGeometry::new(crs, rect)))
+    }
+
+    /// Bounds as a WKT polygon, in crs units.
+    pub fn to_wkt(&self) -> String {
+        let (min, max) = (self.min(), self.max());
+        format!(
+            "POLYGON(({x0} {y0}, {x1} {y0}, {x1} {y1}, {x0} {y1}, {x0} {y0}))",
+            x0 = min.x,
+            y0 = min.y,
+            x1 = max.x,
+            y1 = max.y
+        )
+    }
+
+    /// Bounds as an EWKT polygon (`SRID=<code>;POLYGON(...)`).
+    ///
+    /// [None] if crs has no EPSG code, e.g. custom projections.
+    pub fn to_ewkt(&self) -> Option<String> {
+        let mut spatial_ref = SpatialRef::from_definition(self.crs()).ok()?;
+        if spatial_ref.auth_code().is_err() {
+            // WKT without an authority may still match an EPSG crs.
+            spatial_ref.auto_identify_epsg().ok()?;
+        }
+        if spatial_ref.auth_name().ok()? != "EPSG" {
+            return None;
+        }
+        let srid = spatial_ref.auth_code().ok()?;
+        Some(format!("SRID={srid};{}", self.to_wkt()))
+    }
+
+    /// Bounding rect of a WKT (or EWKT) geometry in `crs`.
+    ///
+    /// An EWKT `SRID` prefix is ignored in favour of `crs`.
+    pub fn from_wkt(wkt: &str, crs: &str) -> Result<GeoBounds> {
+        let wkt = match wkt.split_once(';') {
+            Some((srid, wkt)) if srid.starts_with("SRID=") => wkt,
+            _ => wkt,
+        };
+        let geometry = Geometry::<f64>::try_from_wkt_str(wkt)
+            .map_err(|err| RusterioError::InvalidWkt(err.to_string()))?;
+        let rect = geometry
+            .bounding_rect()
+            .ok_or_else(|| RusterioError::InvalidWkt(String::from("empty geometry")))?;
+        Ok(GeoBounds(CrsGeometry::new(Crs::new(crs), rect)))
+    }
+
     /// Build [ViewBounds] (or pixel bounds) of a raster.
     ///
     /// Transforms [GeoBounds] to [ViewBounds].
@@ -106,23 +261,45 @@ impl GeoBounds {
         &'a self,
         transforms: impl Iterator<Item = &'a GeoReadTransform>,
     ) -> Result<ViewBounds> {
-        let mut read_bounds: Vec<ReadBounds> = transforms
+        let mut read_pixel_shapes = transforms
             .into_iter()
-            .map(|transform| self.as_read_bounds(transform))
-            .collect();
-        let mut view_pixel_shape = read_bounds.pop().unwrap().shape();
-        for read_pixel_shape in read_bounds.into_iter().map(|bounds| bounds.shape()) {
+            .map(|transform| self.read_pixel_shape(transform))
+            .collect::<Result<Vec<Coord<usize>>>>()?;
+        let mut view_pixel_shape = read_pixel_shapes
+            .pop()
+            .ok_or(RusterioError::NoBandsSelected)?;
+        for read_pixel_shape in read_pixel_shapes {
             view_pixel_shape = view_pixel_shape.operate(&read_pixel_shape, num::integer::lcm);
         }
         Ok(ViewBounds(Rect::new(Coord::zero(), view_pixel_shape)))
     }
 
-    pub fn as_read_bounds(&self, transform: &GeoReadTransform) -> ReadBounds {
-        let offset_shape_line = Line::from(self)
+    /// Largest distance, in pixels, that snapping bounds
+    /// to the pixel grid of `transform` moves them.
+    pub fn snap_distance(&self, transform: &GeoReadTransform) -> f64 {
+        let pixel_line = Line::from(self).affine_transform(transform);
+        [pixel_line.start, pixel_line.end]
+            .into_iter()
+            .flat_map(|coord| [coord.x, coord.y])
+            .map(|val| {
+                let frac = val - val.floor();
+                frac.min(1. - frac)
+            })
+            .fold(0., f64::max)
+    }
+
+    pub fn as_read_bounds(&self, transform: &GeoReadTransform) -> Result<ReadBounds> {
+        let offset_shape_line = Line::from(self).affine_transform(transform).try_cast()?;
+        Ok(ReadBounds(offset_shape_line.bounding_rect()))
+    }
+
+    /// (Width, Height) in pixels of `transform`, also for
+    /// bounds extending past its origin, e.g. of a [crate::Raster::stack_union].
+    fn read_pixel_shape(&self, transform: &GeoReadTransform) -> Result<Coord<usize>> {
+        let pixel_line = Line::from(self)
             .affine_transform(transform)
-            .try_cast()
-            .unwrap();
-        ReadBounds(offset_shape_line.bounding_rect())
+            .map_coords(|coord| coord.map_each(f64::floor));
+        pixel_line.delta().map_each(f64::abs).try_cast()
     }
 }
 
@@ -135,7 +312,7 @@ impl GeoBounds {
 ///
 /// In underlaying impl `offset` is given by `.min`,
 /// and `shape` by `(.width, .hight) or .max - .min`.
-#[derive(ambassador::Delegate, Debug)]
+#[derive(ambassador::Delegate, Debug, Clone)]
 #[delegate(GeometryTrait)]
 #[delegate(RectTrait)]
 #[delegate(Area<T>, generics="T", where="T: CoordNum")]
@@ -152,28 +329,72 @@ impl Intersection for ViewBounds {
 impl Bounds for ViewBounds {}
 impl PixelBounds for ViewBounds {}
 
+impl Display for ViewBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_pixel_bounds(f, "ViewBounds", self.offset(), self.shape())
+    }
+}
+
 impl From<&ViewBounds> for Line<usize> {
     fn from(value: &ViewBounds) -> Self {
         Line::new(value.min(), value.max())
     }
 }
 
+/// From (offset, shape).
+impl From<(Coord<usize>, Coord<usize>)> for ViewBounds {
+    fn from((offset, shape): (Coord<usize>, Coord<usize>)) -> Self {
+        Self(Rect::new(offset, offset + shape))
+    }
+}
+
 impl ViewBounds {
     pub fn new(offset: (usize, usize), shape: (usize, usize)) -> Self {
-        let offset = Coord::from(offset);
-        let max = offset + Coord::from(shape);
-        Self(Rect::new(offset, max))
+        Self::from((Coord::from(offset), Coord::from(shape)))
     }
 
-    pub fn as_read_bounds(&self, transform: &ViewReadTransform) -> ReadBounds {
+    /// Iterate over pixel coords within bounds in row-major order.
+    pub fn iter_pixels(&self) -> impl Iterator<Item = Coord<usize>> {
+        iter_pixel_coords(self.min(), self.max())
+    }
+
+    /// Whether pixel at `coord` is within bounds,
+    /// i.e. is yielded by [ViewBounds::iter_pixels].
+    pub fn contains(&self, coord: Coord<usize>) -> bool {
+        let (min, max) = (self.min(), self.max());
+        (min.x..max.x).contains(&coord.x) && (min.y..max.y).contains(&coord.y)
+    }
+
+    /// Bounds grown by `pixels` on every side, with offset clamped at zero.
+    pub fn pad(&self, pixels: usize) -> ViewBounds {
+        let min = self.min().map_each(|val| val.saturating_sub(pixels));
+        let max = self.max().map_each(|val| val + pixels);
+        ViewBounds(Rect::new(min, max))
+    }
+
+    pub fn as_read_bounds(&self, transform: &ViewReadTransform) -> Result<ReadBounds> {
         let offset_shape_line = Line::from(self)
-            .try_cast()
-            .unwrap()
+            .try_cast()?
+            .affine_transform(transform)
+            .map_coords(|coord| coord.map_each(f64::ceil))
+            .try_cast()?;
+        Ok(ReadBounds(offset_shape_line.bounding_rect()))
+    }
+
+    /// Like [ViewBounds::as_read_bounds], as (offset, shape) with the offset
+    /// negative where bounds start before the origin of the read band.
+    pub fn as_signed_read_window(
+        &self,
+        transform: &ViewReadTransform,
+    ) -> Result<(Coord<isize>, Coord<usize>)> {
+        let offset_shape_line: Line<isize> = Line::from(self)
+            .try_cast()?
             .affine_transform(transform)
             .map_coords(|coord| coord.map_each(f64::ceil))
-            .try_cast()
-            .unwrap();
-        ReadBounds(offset_shape_line.bounding_rect())
+            .try_cast()?;
+        let rect = offset_shape_line.bounding_rect();
+        let shape = (rect.max() - rect.min()).try_cast()?;
+        Ok((rect.min(), shape))
     }
 }
 
@@ -201,3 +422,216 @@ impl Intersection for ReadBounds {
 
 impl Bounds for ReadBounds {}
 impl PixelBounds for ReadBounds {}
+
+impl Display for ReadBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_pixel_bounds(f, "ReadBounds", self.offset(), self.shape())
+    }
+}
+
+/// From (offset, shape).
+impl From<(Coord<usize>, Coord<usize>)> for ReadBounds {
+    fn from((offset, shape): (Coord<usize>, Coord<usize>)) -> Self {
+        Self(Rect::new(offset, offset + shape))
+    }
+}
+
+impl ReadBounds {
+    pub fn new(offset: (usize, usize), shape: (usize, usize)) -> Self {
+        Self::from((Coord::from(offset), Coord::from(shape)))
+    }
+
+    /// Iterate over pixel coords within bounds in row-major order.
+    pub fn iter_pixels(&self) -> impl Iterator<Item = Coord<usize>> {
+        iter_pixel_coords(self.min(), self.max())
+    }
+}
+
+fn iter_pixel_coords(min: Coord<usize>, max: Coord<usize>) -> impl Iterator<Item = Coord<usize>> {
+    (min.y..max.y).flat_map(move |y| (min.x..max.x).map(move |x| Coord { x, y }))
+}
+
+fn fmt_pixel_bounds(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    offset: Coord<usize>,
+    shape: Coord<usize>,
+) -> fmt::Result {
+    write!(
+        f,
+        "{name}(offset=({}, {}), shape=({}, {}))",
+        offset.x, offset.y, shape.x, shape.y
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn geo_bounds() -> GeoBounds {
+        GeoBounds::from(CrsGeometry::new(
+            Crs::new("EPSG:32633"),
+            Rect::new((300000., 1290240.), (309800., 1300040.)),
+        ))
+    }
+
+    #[test]
+    fn pixel_grid() {
+        let raster_size = (980, 980);
+        assert_eq!(
+            geo_bounds().pixel_size(raster_size),
+            Coord { x: 10., y: 10. }
+        );
+        let (coord, pixel) = (
+            Coord {
+                x: 300025.,
+                y: 1300005.,
+            },
+            Coord { x: 2.5, y: 3.5 },
+        );
+        assert_eq!(geo_bounds().pixel_position(raster_size, coord), pixel);
+        assert_eq!(geo_bounds().pixel_coord(raster_size, pixel), coord);
+        let transform = geo_bounds().read_geo_transform(raster_size);
+        assert_eq!(
+            (
+                transform.a(),
+                transform.e(),
+                transform.xoff(),
+                transform.yoff()
+            ),
+            (10., -10., 300000., 1300040.)
+        );
+    }
+
+    #[test]
+    fn from_offset_shape_coords() {
+        let offset = Coord { x: 2, y: 3 };
+        let shape = Coord { x: 4, y: 5 };
+        let view_bounds = ViewBounds::from((offset, shape));
+        assert_eq!(view_bounds.offset(), offset);
+        assert_eq!(view_bounds.shape(), shape);
+        let read_bounds = ReadBounds::from((offset, shape));
+        assert_eq!(
+            read_bounds.to_string(),
+            ReadBounds::new((2, 3), (4, 5)).to_string()
+        );
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            geo_bounds().to_string(),
+            "GeoBounds(min=(300000, 1290240), max=(309800, 1300040), crs=EPSG:32633)"
+        );
+        assert_eq!(
+            ViewBounds::new((2, 3), (4, 5)).to_string(),
+            "ViewBounds(offset=(2, 3), shape=(4, 5))"
+        );
+    }
+
+    #[test]
+    fn snap_distance() {
+        let transform =
+            ReadGeoTransform::new(10., 0., 300000., 0., -10., 1300040., Crs::new("EPSG:32633"))
+                .try_inverse()
+                .unwrap();
+        assert_eq!(geo_bounds().snap_distance(&transform), 0.);
+        let shifted = GeoBounds::from(CrsGeometry::new(
+            Crs::new("EPSG:32633"),
+            Rect::new((300007., 1290240.), (309800., 1300040.)),
+        ));
+        assert!((shifted.snap_distance(&transform) - 0.3).abs() < 1e-9);
+        // 0.9 pixels past a grid line is 0.1 pixels before the next one.
+        let shifted = GeoBounds::from(CrsGeometry::new(
+            Crs::new("EPSG:32633"),
+            Rect::new((300009., 1290240.), (309800., 1300040.)),
+        ));
+        assert!((shifted.snap_distance(&transform) - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn wkt_round_trip() {
+        let wkt = geo_bounds().to_wkt();
+        assert_eq!(
+            wkt,
+            "POLYGON((300000 1290240, 309800 1290240, 309800 1300040, 300000 1300040, 300000 1290240))"
+        );
+        let parsed = GeoBounds::from_wkt(&wkt, "EPSG:32633").unwrap();
+        assert_eq!(parsed.min(), geo_bounds().min());
+        assert_eq!(parsed.max(), geo_bounds().max());
+    }
+
+    #[test]
+    fn ewkt_round_trip() {
+        let ewkt = geo_bounds().to_ewkt().unwrap();
+        assert!(ewkt.starts_with("SRID=32633;POLYGON(("));
+        let parsed = GeoBounds::from_wkt(&ewkt, "EPSG:32633").unwrap();
+        assert_eq!(parsed.max(), geo_bounds().max());
+    }
+
+    #[test]
+    fn iter_pixels() {
+        let view_bounds = ViewBounds::new((1, 2), (3, 2));
+        let pixels: Vec<Coord<usize>> = view_bounds.iter_pixels().collect();
+        assert_eq!(pixels.len(), view_bounds.size());
+        assert_eq!(pixels.first(), Some(&Coord { x: 1, y: 2 }));
+        assert_eq!(pixels[3], Coord { x: 1, y: 3 });
+        assert_eq!(pixels.last(), Some(&Coord { x: 3, y: 3 }));
+        let read_bounds = ReadBounds::new((1, 2), (3, 2));
+        assert!(read_bounds.iter_pixels().eq(view_bounds.iter_pixels()));
+    }
+
+    #[test]
+    fn contains() {
+        let view_bounds = ViewBounds::new((1, 2), (3, 2));
+        let contained: Vec<Coord<usize>> = ViewBounds::new((0, 0), (6, 6))
+            .iter_pixels()
+            .filter(|coord| view_bounds.contains(*coord))
+            .collect();
+        assert!(contained.into_iter().eq(view_bounds.iter_pixels()));
+        assert!(!view_bounds.contains(Coord { x: 4, y: 2 }));
+        assert!(!view_bounds.contains(Coord { x: 1, y: 4 }));
+    }
+
+    #[test]
+    fn geo_bounds_pad() {
+        let padded = geo_bounds().pad(20.);
+        assert_eq!(padded.crs(), "EPSG:32633");
+        assert_eq!(
+            padded.min(),
+            Coord {
+                x: 299980.,
+                y: 1290220.
+            }
+        );
+        assert_eq!(
+            padded.max(),
+            Coord {
+                x: 309820.,
+                y: 1300060.
+            }
+        );
+        // Shrinking past the center collapses to it.
+        let collapsed = geo_bounds().pad(-10000.);
+        assert_eq!(collapsed.min(), collapsed.max());
+        assert_eq!(
+            collapsed.min(),
+            Coord {
+                x: 304900.,
+                y: 1295140.
+            }
+        );
+    }
+
+    #[test]
+    fn view_bounds_pad() {
+        let padded = ViewBounds::new((3, 1), (4, 2)).pad(2);
+        assert_eq!(padded.offset(), Coord { x: 1, y: 0 });
+        assert_eq!(padded.shape(), Coord { x: 8, y: 5 });
+    }
+
+    #[test]
+    fn invalid_wkt() {
+        assert!(GeoBounds::from_wkt("POLYGON((", "EPSG:32633").is_err());
+    }
+}
@@ -0,0 +1,290 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use geo::Coord;
+use geo_traits::RectTrait;
+
+use crate::{
+    buffer::Buffer,
+    components::{
+        band::BandReader,
+        bounds::{PixelBounds, ReadBounds},
+        engines::validate_read_bounds,
+        resampling::GdalResamplingAlgorithm,
+        DataType,
+    },
+    errors::Result,
+};
+
+/// (Source key, block column, block row).
+type BlockKey = (u64, usize, usize);
+
+/// Least recently used cache of decoded band blocks,
+/// holding at most `budget_bytes` of pixel data.
+///
+/// Can be shared by the readers of many bands and files.
+#[derive(Debug)]
+pub struct BlockCache<T> {
+    budget_bytes: usize,
+    state: Mutex<LruState<T>>,
+}
+
+#[derive(Debug)]
+struct LruState<T> {
+    blocks: HashMap<BlockKey, (Arc<[T]>, u64)>,
+    /// Keys by last use.
+    recency: BTreeMap<u64, BlockKey>,
+    tick: u64,
+    bytes: usize,
+}
+
+impl<T> BlockCache<T> {
+    pub fn new(budget_bytes: usize) -> Self {
+        Self {
+            budget_bytes,
+            state: Mutex::new(LruState {
+                blocks: HashMap::new(),
+                recency: BTreeMap::new(),
+                tick: 0,
+                bytes: 0,
+            }),
+        }
+    }
+
+    /// Bytes of pixel data currently cached.
+    pub fn size_bytes(&self) -> usize {
+        self.lock().bytes
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LruState<T>> {
+        // Cache state is consistent after each operation, so poisoning is harmless.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn get(&self, key: &BlockKey) -> Option<Arc<[T]>> {
+        let mut state = self.lock();
+        state.tick += 1;
+        let tick = state.tick;
+        let (block, last_used) = state.blocks.get_mut(key)?;
+        let block = Arc::clone(block);
+        let previous_use = std::mem::replace(last_used, tick);
+        state.recency.remove(&previous_use);
+        state.recency.insert(tick, *key);
+        Some(block)
+    }
+
+    /// Blocks larger than the whole budget are not cached.
+    fn insert(&self, key: BlockKey, block: Arc<[T]>) {
+        let block_bytes = std::mem::size_of_val(block.as_ref());
+        if block_bytes > self.budget_bytes {
+            return;
+        }
+        let mut state = self.lock();
+        while state.bytes + block_bytes > self.budget_bytes {
+            let Some((_, evicted_key)) = state.recency.pop_first() else {
+                break;
+            };
+            if let Some((evicted, _)) = state.blocks.remove(&evicted_key) {
+                state.bytes -= std::mem::size_of_val(evicted.as_ref());
+            }
+        }
+        state.tick += 1;
+        let tick = state.tick;
+        state.recency.insert(tick, key);
+        state.bytes += block_bytes;
+        if let Some((replaced, last_used)) = state.blocks.insert(key, (block, tick)) {
+            state.recency.remove(&last_used);
+            state.bytes -= std::mem::size_of_val(replaced.as_ref());
+        }
+    }
+}
+
+/// [BandReader] reading whole (Width, Height) `block_size` blocks
+/// through a [BlockCache] before hitting the wrapped reader.
+///
+/// Blocks are keyed by [BandReader::hash_source] and block size,
+/// so readers of the same band share cached blocks.
+#[derive(Debug)]
+pub struct CachedBandReader<T: DataType> {
+    /// Hash of the source and block size of `reader`.
+    source_key: u64,
+    reader: Arc<dyn BandReader<T>>,
+    cache: Arc<BlockCache<T>>,
+    block_size: (usize, usize),
+}
+
+impl<T: DataType> CachedBandReader<T> {
+    pub fn new(
+        reader: Arc<dyn BandReader<T>>,
+        cache: Arc<BlockCache<T>>,
+        block_size: (usize, usize),
+    ) -> Self {
+        let mut hasher = DefaultHasher::new();
+        reader.hash_source(&mut hasher);
+        block_size.hash(&mut hasher);
+        Self {
+            source_key: hasher.finish(),
+            reader,
+            cache,
+            block_size,
+        }
+    }
+
+    /// Block at `block_idx` (column, row), read if not cached.
+    fn block(&self, block_idx: Coord<usize>, raster_size: (usize, usize)) -> Result<Arc<[T]>> {
+        let key = (self.source_key, block_idx.x, block_idx.y);
+        if let Some(block) = self.cache.get(&key) {
+            return Ok(block);
+        }
+        let (block_width, block_height) = self.block_size;
+        let offset = (block_idx.x * block_width, block_idx.y * block_height);
+        let shape = (
+            block_width.min(raster_size.0 - offset.0),
+            block_height.min(raster_size.1 - offset.1),
+        );
+        let block: Arc<[T]> = Arc::from(
+            self.reader
+                .read_to_buffer(&ReadBounds::new(offset, shape))?
+                .as_slice(),
+        );
+        self.cache.insert(key, Arc::clone(&block));
+        Ok(block)
+    }
+}
+
+impl<T: DataType> BandReader<T> for CachedBandReader<T> {
+    fn raster_size(&self) -> Result<(usize, usize)> {
+        self.reader.raster_size()
+    }
+    fn read_into_slice(&self, bounds: &ReadBounds, slice: &mut [T]) -> Result<()> {
+        let raster_size = self.raster_size()?;
+        validate_read_bounds(bounds, raster_size)?;
+        if bounds.size() == 0 {
+            return Ok(());
+        }
+        let (block_width, block_height) = self.block_size;
+        let (min, max) = (bounds.min(), bounds.max());
+        let width = max.x - min.x;
+        for block_row in min.y / block_height..max.y.div_ceil(block_height) {
+            for block_col in min.x / block_width..max.x.div_ceil(block_width) {
+                let block = self.block(Coord::from((block_col, block_row)), raster_size)?;
+                let block_min = Coord {
+                    x: block_col * block_width,
+                    y: block_row * block_height,
+                };
+                let block_max = Coord {
+                    x: (block_min.x + block_width).min(raster_size.0),
+                    y: (block_min.y + block_height).min(raster_size.1),
+                };
+                let block_stride = block_max.x - block_min.x;
+                let (col_start, col_end) = (min.x.max(block_min.x), max.x.min(block_max.x));
+                for row in min.y.max(block_min.y)..max.y.min(block_max.y) {
+                    let block_start = (row - block_min.y) * block_stride + col_start - block_min.x;
+                    let slice_start = (row - min.y) * width + col_start - min.x;
+                    slice[slice_start..slice_start + col_end - col_start]
+                        .copy_from_slice(&block[block_start..block_start + col_end - col_start]);
+                }
+            }
+        }
+        Ok(())
+    }
+    fn read_to_buffer(&self, bounds: &ReadBounds) -> Result<Buffer<T, 1>> {
+        let mut buff = Buffer::new([bounds.size()]);
+        self.read_into_slice(bounds, buff.as_mut()).map(|_| buff)
+    }
+    fn read_pixel(&self, offset: Coord<usize>) -> Result<T> {
+        let pixel_buff = &mut [T::zero()];
+        self.read_into_slice(&ReadBounds::new(offset.x_y(), (1, 1)), pixel_buff)?;
+        Ok(pixel_buff[0])
+    }
+    /// Bypasses the cache.
+    fn read_into_slice_resampled(
+        &self,
+        bounds: &ReadBounds,
+        out_shape: (usize, usize),
+        slice: &mut [T],
+        algorithm: GdalResamplingAlgorithm,
+    ) -> Result<()> {
+        self.reader
+            .read_into_slice_resampled(bounds, out_shape, slice, algorithm)
+    }
+    /// Bypasses the cache.
+    fn read_overview_into_slice(
+        &self,
+        level: usize,
+        bounds: &ReadBounds,
+        slice: &mut [T],
+    ) -> Result<()> {
+        self.reader.read_overview_into_slice(level, bounds, slice)
+    }
+    fn resamples_natively(&self) -> bool {
+        self.reader.resamples_natively()
+    }
+    fn hash_source(&self, state: &mut dyn Hasher) {
+        self.reader.hash_source(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::engines::mem_engine::InMemoryBandReader;
+
+    /// Reader of a 5x5 band holding `0..25` through a cache of `budget_bytes`.
+    fn cached_reader(budget_bytes: usize) -> (CachedBandReader<u16>, Arc<BlockCache<u16>>) {
+        let reader = Arc::new(InMemoryBandReader::new((0..25).collect(), (5, 5)).unwrap());
+        let cache = Arc::new(BlockCache::new(budget_bytes));
+        (
+            CachedBandReader::new(reader, Arc::clone(&cache), (2, 2)),
+            cache,
+        )
+    }
+
+    #[test]
+    fn reads_across_blocks() {
+        let (reader, cache) = cached_reader(1024);
+        let buff = reader
+            .read_to_buffer(&ReadBounds::new((1, 1), (3, 4)))
+            .unwrap();
+        assert_eq!(
+            buff.as_ref(),
+            &[6, 7, 8, 11, 12, 13, 16, 17, 18, 21, 22, 23]
+        );
+        // 2x2, 2x2, 2x2, 2x2, 2x1 and 2x1 blocks of u16.
+        assert_eq!(cache.size_bytes(), (4 * 4 + 2 * 2) * 2);
+        assert_eq!(reader.read_pixel(Coord { x: 4, y: 4 }).unwrap(), 24);
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        // Room for two 2x2 blocks of u16.
+        let (reader, cache) = cached_reader(16);
+        reader
+            .read_to_buffer(&ReadBounds::new((0, 0), (4, 2)))
+            .unwrap();
+        // Use block (0, 0) again, so block (1, 0) is evicted next.
+        reader.read_pixel(Coord { x: 0, y: 0 }).unwrap();
+        reader.read_pixel(Coord { x: 0, y: 2 }).unwrap();
+        assert_eq!(cache.size_bytes(), 16);
+        assert!(cache.get(&(reader.source_key, 0, 0)).is_some());
+        assert!(cache.get(&(reader.source_key, 1, 0)).is_none());
+    }
+
+    #[test]
+    fn shares_blocks_of_same_source() {
+        let band = Arc::new(InMemoryBandReader::new((0..25).collect(), (5, 5)).unwrap());
+        let cache = Arc::new(BlockCache::<u16>::new(1024));
+        let first = CachedBandReader::new(band.clone(), Arc::clone(&cache), (2, 2));
+        let second = CachedBandReader::new(band.clone(), Arc::clone(&cache), (2, 2));
+        first.read_pixel(Coord { x: 0, y: 0 }).unwrap();
+        assert_eq!(second.read_pixel(Coord { x: 1, y: 1 }).unwrap(), 6);
+        assert_eq!(cache.size_bytes(), 4 * 2);
+        // Other block sizes don't share blocks.
+        let other = CachedBandReader::new(band, Arc::clone(&cache), (3, 3));
+        other.read_pixel(Coord { x: 0, y: 0 }).unwrap();
+        assert_eq!(cache.size_bytes(), (4 + 9) * 2);
+    }
+}
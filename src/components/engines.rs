@@ -1,150 +1,881 @@
-use std::{collections::HashMap, fmt::Debug, marker::PhantomData, path::Path, rc::Rc, sync::Arc};
+use std::{
+    ffi::OsStr,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+};
 
 use crate::{
     components::{
         band::{BandInfo, BandReader},
-        bounds::{Bounds, GeoBounds, ReadBounds},
+        bounds::{Bounds, GeoBounds, PixelBounds, ReadBounds},
+        cache::{BlockCache, CachedBandReader},
         file::File,
         raster::band::RasterBand,
+        resampling::GdalResamplingAlgorithm,
         transforms::ReadGeoTransform,
         DataType, Metadata,
     },
-    errors::Result,
-    try_tuple_cast, Indexes, Raster,
+    errors::{Result, RusterioError},
+    CoordUtils, Crs, Indexes, Raster,
 };
+use geo::Coord;
+use geo_traits::RectTrait;
+
+/// Fails with [RusterioError::ReadBoundsOutOfRange]
+/// if `bounds` don't fit in `raster_size` (Width, Height).
+pub(crate) fn validate_read_bounds(bounds: &ReadBounds, raster_size: (usize, usize)) -> Result<()> {
+    let max = bounds.max();
+    if max.x > raster_size.0 || max.y > raster_size.1 {
+        return Err(RusterioError::ReadBoundsOutOfRange {
+            offset: bounds.offset().x_y(),
+            shape: bounds.shape().x_y(),
+            raster_size,
+        });
+    }
+    Ok(())
+}
+
+/// Smallest bounds of whole `block_size` blocks containing `bounds`,
+/// cropped to `raster_size`.
+fn block_aligned_bounds(
+    bounds: &ReadBounds,
+    block_size: (usize, usize),
+    raster_size: (usize, usize),
+) -> ReadBounds {
+    let block_size = Coord::from(block_size);
+    let min = bounds.min();
+    let max = bounds.max();
+    let aligned_min = min.operate(&block_size, |val, block| val / block * block);
+    let aligned_max = max
+        .operate(&block_size, |val, block| val.next_multiple_of(block))
+        .operate(&Coord::from(raster_size), usize::min);
+    ReadBounds::from((aligned_min, aligned_max - aligned_min))
+}
 
 /// Implementations for gdal
 pub mod gdal_engine {
 
-    use crate::{components::bounds::PixelBounds, crs_geo::CrsGeometry, Buffer, CoordUtils};
+    use crate::{buffer::Stretch, Buffer, CoordUtils};
 
     use super::*;
     use gdal::{
-        raster::{GdalType, RasterBand as GdalRasterBand},
-        Dataset as GdalDataset, Metadata as GdalMetadata, MetadataEntry as GdalMetadataEntry,
+        raster::{Buffer as GdalBuffer, GdalType, RasterBand as GdalRasterBand, ResampleAlg},
+        spatial_ref::SpatialRef,
+        Dataset as GdalDataset, DatasetOptions, Metadata as GdalMetadata,
+        MetadataEntry as GdalMetadataEntry,
+    };
+    use geo::Coord;
+    use log::{debug, info, warn};
+    use rayon::prelude::*;
+    use std::{
+        cell::{Cell, RefCell},
+        collections::HashMap,
+        time::SystemTime,
     };
-    use geo::{AffineOps, Coord, Point, Rect};
-    use geo_traits::RectTrait;
-    use log::info;
 
-    fn filter_metadata_gdal(metadata: &impl GdalMetadata) -> HashMap<String, String> {
+    /// Metadata domains read when none are specified.
+    const DEFAULT_METADATA_DOMAINS: &[&str] = &[""];
+
+    /// Keep metadata entries in `domains`.
+    ///
+    /// Entries outside the default (empty) domain are keyed as `"{domain}:{key}"`.
+    fn filter_metadata_gdal(metadata: &impl GdalMetadata, domains: &[impl AsRef<str>]) -> Metadata {
         GdalMetadata::metadata(metadata)
             .filter_map(|GdalMetadataEntry { domain, key, value }| {
-                if domain.eq("") {
+                if !domains.iter().any(|included| included.as_ref().eq(&domain)) {
+                    None
+                } else if domain.is_empty() {
                     Some((key, value))
                 } else {
-                    None
+                    Some((format!("{domain}:{key}"), value))
                 }
             })
             .collect()
     }
 
+    /// Subdataset descriptions (`SUBDATASET_<n>_DESC`),
+    /// keyed as `"SUBDATASETS:SUBDATASET_<n>_DESC"`.
+    fn subdataset_descriptions(metadata: &impl GdalMetadata) -> Vec<(String, String)> {
+        GdalMetadata::metadata(metadata)
+            .filter(|GdalMetadataEntry { domain, key, .. }| {
+                domain.eq("SUBDATASETS") && key.starts_with("SUBDATASET_") && key.ends_with("_DESC")
+            })
+            .map(|GdalMetadataEntry { domain, key, value }| (format!("{domain}:{key}"), value))
+            .collect()
+    }
+
     #[derive(thiserror::Error, Debug)]
     pub enum GdalEngineError {
         #[error("Driver {0} can not be used for this path.")]
         WrongDriver(String),
+        #[error("{0} is not a Sentinel-2 SAFE product.")]
+        NotSafeProduct(String),
+        #[error("Metadata key {key} not found in {object_desc}.")]
+        MetadataKeyNotFound {
+            object_desc: String,
+            key: &'static str,
+        },
+        #[error("Could not detect Sentinel-2 processing level of {0}.")]
+        UnknownSentinel2Level(String),
+        #[error("No {resolution}m resolution group in {path}, available: {available:?}.")]
+        ResolutionGroupNotFound {
+            path: String,
+            resolution: u32,
+            available: Vec<String>,
+        },
+        #[error("{0} is not a Landsat 8 or 9 Collection 2 product.")]
+        NotLandsatProduct(String),
+        #[error("Product file {0} not found.")]
+        ProductFileNotFound(String),
+    }
+
+    /// Sentinel-2 processing level, which sets the subdataset layout.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Sentinel2Level {
+        /// Top of atmosphere reflectance.
+        L1C,
+        /// Bottom of atmosphere reflectance.
+        L2A,
+    }
+
+    impl Sentinel2Level {
+        /// Detect level from a metadata file path,
+        /// which contains `MSIL1C` or `MSIL2A`.
+        pub fn from_path(path: &str) -> Option<Self> {
+            if path.contains("MSIL2A") {
+                Some(Self::L2A)
+            } else if path.contains("MSIL1C") {
+                Some(Self::L1C)
+            } else {
+                None
+            }
+        }
+
+        /// Bands to read from the 10m, 20m and 60m subdatasets.
+        fn subdataset_indexes(self) -> [Indexes; 3] {
+            match self {
+                // 60m holds B1, B9, B10.
+                Self::L1C => [Indexes::all(), Indexes::all(), Indexes::all()],
+                // 60m holds B1, B9 followed by derived products.
+                Self::L2A => [Indexes::all(), Indexes::all(), Indexes::from([0usize, 1])],
+            }
+        }
     }
 
+    /// Types GDAL converts band data into when reading,
+    /// matched by [GdalType::gdal_ordinal].
     pub trait GdalDataType: DataType + GdalType {}
+    impl GdalDataType for u8 {}
+    impl GdalDataType for i8 {}
     impl GdalDataType for u16 {}
+    impl GdalDataType for i16 {}
+    impl GdalDataType for u32 {}
+    impl GdalDataType for i32 {}
+    impl GdalDataType for f32 {}
+    impl GdalDataType for f64 {}
+
+    /// Products [open] stacks from several datasets, detected in order.
+    const SENSORS: &[&dyn Sensor] = &[&Sentinel2, &Landsat];
+
+    /// Multi dataset product of a sensor, e.g. [Sentinel2].
+    pub trait Sensor: Sync {
+        /// Whether the product at `path` is of this sensor,
+        /// given the short name of the GDAL driver that opens it, if any.
+        fn detect(&self, path: &Path, driver_name: Option<&str>) -> bool;
+        /// Paths of datasets holding the product bands,
+        /// with the bands to read from each, in stacking order.
+        fn band_groups(&self, path: &Path) -> Result<Vec<(String, Indexes)>>;
+        /// Names of stacked bands, if not those in the datasets.
+        fn band_names(&self, _path: &Path) -> Option<Vec<String>> {
+            None
+        }
+    }
 
+    /// Open a raster at `path`, stacking the datasets of
+    /// products detected by a [Sensor], or all bands of the dataset otherwise.
+    ///
+    /// Fails with [RusterioError::UnsupportedDriver] if no [Sensor] detects
+    /// the product and its dataset has no raster bands, e.g. a product
+    /// container of an unknown sensor. Other errors opening the dataset
+    /// as a plain raster, e.g. [RusterioError::NoCrsFound], are returned as is.
     pub fn open<T: GdalDataType>(path: impl AsRef<Path>) -> Result<Raster<T>> {
-        if let Ok(raster) = Raster::new::<GdalFile<T>>(&path, Indexes::all()) {
+        open_with(path, &OpenOptions::default())
+    }
+
+    /// Like [open], opening every dataset with `options`.
+    pub fn open_with<T: GdalDataType>(
+        path: impl AsRef<Path>,
+        options: &OpenOptions,
+    ) -> Result<Raster<T>> {
+        let path = path.as_ref();
+        let open_file = |path: &Path| {
+            GdalFileBuilder::<T>::new(path)
+                .open_options(options.clone())
+                .open()
+        };
+        let dataset = options.open_dataset(path);
+        let driver_name = dataset
+            .as_ref()
+            .ok()
+            .map(|dataset| dataset.driver().short_name());
+        let sensor = SENSORS
+            .iter()
+            .find(|sensor| sensor.detect(path, driver_name.as_deref()));
+        if let Some(sensor) = sensor {
+            let rasters = sensor
+                .band_groups(path)?
+                .into_iter()
+                .map(|(group_path, indexes)| {
+                    Raster::from_file(open_file(Path::new(&group_path))?, indexes)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let mut raster = Raster::stack(rasters)?;
+            if let Some(band_names) = sensor.band_names(path) {
+                raster.rename_bands(band_names);
+            }
             return Ok(raster);
-        } else {
-            let dataset = GdalDataset::open(&path)?;
-            match dataset.driver().short_name().as_str() {
-                // TODO: Probably there is a better way to do this
-                "SENTINEL2" => {
-                    let sub_dataset_paths = (1..=3)
+        }
+        let dataset = dataset?;
+        if dataset.raster_count() == 0 {
+            return Err(RusterioError::UnsupportedDriver(
+                dataset.driver().short_name(),
+            ));
+        }
+        Raster::from_file(open_file(path)?, Indexes::all())
+    }
+
+    /// Open only the `resolution` meters group of a Sentinel-2 product,
+    /// e.g. 10 for bands B4, B3, B2 and B8.
+    ///
+    /// Fails with [GdalEngineError::ResolutionGroupNotFound]
+    /// if the product has no such group.
+    pub fn open_resolution<T: GdalDataType>(
+        path: impl AsRef<Path>,
+        resolution: u32,
+    ) -> Result<Raster<T>> {
+        let path = path.as_ref();
+        let dataset = GdalDataset::open(path)?;
+        let driver_name = dataset.driver().short_name();
+        if driver_name.ne("SENTINEL2") {
+            return Err(GdalEngineError::WrongDriver(driver_name).into());
+        }
+        let band_groups = Sentinel2.band_groups(path)?;
+        // Group paths look like `SENTINEL2_L2A:{metadata path}:10:EPSG_32633`.
+        let group_resolution = |group_path: &str| group_path.rsplit(':').nth(1).map(String::from);
+        let available: Vec<String> = band_groups
+            .iter()
+            .filter_map(|(group_path, _)| group_resolution(group_path))
+            .collect();
+        let (group_path, indexes) = band_groups
+            .into_iter()
+            .find(|(group_path, _)| group_resolution(group_path) == Some(resolution.to_string()))
+            .ok_or_else(|| GdalEngineError::ResolutionGroupNotFound {
+                path: path.display().to_string(),
+                resolution,
+                available,
+            })?;
+        Raster::new::<GdalFile<T>>(group_path, indexes)
+    }
+
+    /// Write a (C, H, W) `buffer` covering `bounds` as a GeoTIFF at `path`,
+    /// with a band per channel described by `band_names`.
+    pub fn write_geotiff<T: GdalDataType>(
+        path: impl AsRef<Path>,
+        buffer: &Buffer<T, 3>,
+        bounds: &GeoBounds,
+        band_names: &[String],
+    ) -> Result<()> {
+        let [channels, height, width] = buffer.shape();
+        if band_names.len() != channels {
+            return Err(RusterioError::BandCountMismatch {
+                expected: channels,
+                found: band_names.len(),
+            });
+        }
+        let mut dataset = gdal::DriverManager::get_driver_by_name("GTiff")?
+            .create_with_band_type::<T, _>(path, width, height, channels)?;
+        dataset.set_geo_transform(&bounds.geo_transform((width, height)))?;
+        dataset.set_projection(&SpatialRef::from_definition(bounds.crs())?.to_wkt()?)?;
+        for ((band_idx, channel), name) in buffer
+            .as_ref()
+            .chunks(width * height)
+            .enumerate()
+            .zip(band_names)
+        {
+            let mut rasterband = dataset.rasterband(band_idx + 1)?;
+            let mut channel_buffer = GdalBuffer::new((width, height), channel.to_vec());
+            rasterband.write((0, 0), (width, height), &mut channel_buffer)?;
+            rasterband.set_description(name)?;
+        }
+        Ok(())
+    }
+
+    impl<T: GdalDataType> Raster<T> {
+        /// Stack the first band of each file in `dir` matching `glob_pattern`,
+        /// e.g. one GeoTIFF per band.
+        ///
+        /// Bands are named by file stem and ordered by `order`.
+        pub fn from_directory(dir: &Path, glob_pattern: &str, order: SortOrder) -> Result<Self> {
+            let pattern = dir.join(glob_pattern);
+            let paths = glob::glob(&pattern.to_string_lossy())?
+                .collect::<std::result::Result<Vec<PathBuf>, _>>()?;
+            let paths = order.sort(paths)?;
+            if paths.is_empty() {
+                return Err(RusterioError::NoFilesMatched {
+                    dir: dir.to_path_buf(),
+                    pattern: glob_pattern.to_string(),
+                });
+            }
+            let rasters = paths
+                .iter()
+                .map(|path| {
+                    let mut raster = Raster::new::<GdalFile<T>>(path, Indexes::from([0usize]))?;
+                    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                    raster.rename_bands([stem.to_string()]);
+                    Ok(raster)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Raster::stack(rasters)
+        }
+
+        /// Open a Sentinel-2 SAFE product,
+        /// either zipped (`*.SAFE.zip`) or extracted (`*.SAFE` directory).
+        pub fn open_safe_product(path: impl AsRef<Path>) -> Result<Self> {
+            let path = path.as_ref();
+            let not_safe_product = || GdalEngineError::NotSafeProduct(path.display().to_string());
+            let file_name = path
+                .file_name()
+                .and_then(OsStr::to_str)
+                .ok_or_else(not_safe_product)?;
+            let (safe_name, is_zip) = match file_name.strip_suffix(".zip") {
+                Some(safe_name) => (safe_name, true),
+                None => (file_name, false),
+            };
+            if !safe_name.ends_with(".SAFE") {
+                return Err(not_safe_product().into());
+            }
+            // Product names look like `S2B_MSIL2A_...`
+            let product_type = safe_name.split('_').nth(1).ok_or_else(not_safe_product)?;
+            let metadata_file_name = format!("MTD_{product_type}.xml");
+            let metadata_path = if is_zip {
+                PathBuf::from(format!("/vsizip/{}", path.display()))
+                    .join(safe_name)
+                    .join(metadata_file_name)
+            } else {
+                path.join(metadata_file_name)
+            };
+            open(metadata_path)
+        }
+    }
+
+    /// Order of bands in [Raster::from_directory].
+    #[derive(Debug, Clone)]
+    pub enum SortOrder {
+        /// By file path.
+        Alphabetical,
+        /// Oldest modified file first.
+        ModificationTime,
+        /// In the order of listed paths, matched by full path or file name.
+        /// Files not listed are left out.
+        Explicit(Vec<PathBuf>),
+    }
+
+    impl SortOrder {
+        fn sort(&self, mut paths: Vec<PathBuf>) -> Result<Vec<PathBuf>> {
+            match self {
+                SortOrder::Alphabetical => paths.sort(),
+                SortOrder::ModificationTime => {
+                    let mut modified = paths
                         .into_iter()
-                        .map(|sub_dataset_idx| {
-                            // Items should exist always
-                            dataset
-                                .metadata_item(
-                                    format!("SUBDATASET_{sub_dataset_idx}_NAME").as_str(),
-                                    "SUBDATASETS",
-                                )
-                                .unwrap()
-                        })
-                        .zip([
-                            (Indexes::all()),
-                            (Indexes::all()),
-                            (Indexes::from([0usize, 1])),
-                        ])
-                        .map(|(path, indexes)| Raster::new::<GdalFile<T>>(path, indexes))
+                        .map(|path| Ok((std::fs::metadata(&path)?.modified()?, path)))
                         .collect::<Result<Vec<_>>>()?;
-                    return Raster::stack(sub_dataset_paths);
+                    modified.sort();
+                    paths = modified.into_iter().map(|(_, path)| path).collect();
+                }
+                SortOrder::Explicit(order) => {
+                    paths = order
+                        .iter()
+                        .filter_map(|ordered| {
+                            paths
+                                .iter()
+                                .find(|path| {
+                                    path.as_path().eq(ordered)
+                                        || path.file_name().eq(&Some(ordered.as_os_str()))
+                                })
+                                .cloned()
+                        })
+                        .collect();
                 }
-                _ => unimplemented!(),
             }
+            Ok(paths)
+        }
+    }
+
+    /// Sentinel-2 product helpers.
+    pub struct Sentinel2;
+
+    /// Products opened by the GDAL `SENTINEL2` driver,
+    /// stacking the bands of the 10m, 20m and 60m subdatasets.
+    impl Sensor for Sentinel2 {
+        fn detect(&self, _path: &Path, driver_name: Option<&str>) -> bool {
+            driver_name == Some("SENTINEL2")
+        }
+
+        fn band_groups(&self, path: &Path) -> Result<Vec<(String, Indexes)>> {
+            let path_str = path.to_string_lossy();
+            let level = Sentinel2Level::from_path(&path_str)
+                .ok_or_else(|| GdalEngineError::UnknownSentinel2Level(path_str.to_string()))?;
+            let dataset = GdalDataset::open(path)?;
+            // Subdatasets are the 10m, 20m and 60m resolution groups.
+            let keys = [
+                "SUBDATASET_1_NAME",
+                "SUBDATASET_2_NAME",
+                "SUBDATASET_3_NAME",
+            ];
+            level
+                .subdataset_indexes()
+                .into_iter()
+                .zip(keys)
+                .map(|(indexes, key)| {
+                    let sub_dataset_path =
+                        dataset.metadata_item(key, "SUBDATASETS").ok_or_else(|| {
+                            GdalEngineError::MetadataKeyNotFound {
+                                object_desc: path_str.to_string(),
+                                key,
+                            }
+                        })?;
+                    Ok((sub_dataset_path, indexes))
+                })
+                .collect()
         }
     }
 
+    impl Sentinel2 {
+        /// Open a Sentinel-2 product and read its true color bands
+        /// (B4, B3, B2 at 10m) as a display ready RGB [Buffer] (C, H, W).
+        ///
+        /// Each channel is linearly stretched between its 2% and 98% percentiles.
+        ///
+        /// Fails as [open_resolution] does if the product has no 10m group.
+        pub fn true_color_rgb(
+            path: impl AsRef<Path>,
+            clip: Option<GeoBounds>,
+        ) -> Result<Buffer<u8, 3>> {
+            let raster = open_resolution::<u16>(path, 10)?;
+            raster
+                .view(clip, Indexes::by_name(["B4", "B3", "B2"]))?
+                .to_rgb8([0, 1, 2], Stretch::Percentile(0.02, 0.98))
+        }
+    }
+
+    /// Landsat 8 and 9 Collection 2 product helpers.
+    ///
+    /// Products are directories of single band GeoTIFFs, e.g.
+    /// `LC08_L2SP_193052_20241206_20241211_02_T1/LC08_L2SP_193052_20241206_20241211_02_T1_SR_B4.TIF`.
+    pub struct Landsat;
+
+    impl Landsat {
+        /// Product directory and id of a product directory
+        /// or of its `_MTL.txt` metadata file.
+        fn product(path: &Path) -> Option<(&Path, &str)> {
+            let file_name = path.file_name()?.to_str()?;
+            let (dir, product_id) = match file_name.strip_suffix("_MTL.txt") {
+                Some(product_id) => (path.parent()?, product_id),
+                None => (path, file_name),
+            };
+            // Ids look like `LC08_L2SP_193052_20241206_20241211_02_T1`.
+            let parts: Vec<&str> = product_id.split('_').collect();
+            let is_landsat = matches!(parts.as_slice(), ["LC08" | "LC09", _, _, _, _, "02", _]);
+            is_landsat.then_some((dir, product_id))
+        }
+
+        /// Bands of products at processing `level`, in stacking order.
+        ///
+        /// 30m reflective bands come first, then thermal bands,
+        /// delivered at 30m from 100m native, then the 15m panchromatic band.
+        fn level_bands(level: &str) -> Option<&'static [&'static str]> {
+            match level {
+                "L2SP" => Some(&[
+                    "SR_B1", "SR_B2", "SR_B3", "SR_B4", "SR_B5", "SR_B6", "SR_B7", "ST_B10",
+                ]),
+                "L2SR" => Some(&[
+                    "SR_B1", "SR_B2", "SR_B3", "SR_B4", "SR_B5", "SR_B6", "SR_B7",
+                ]),
+                "L1TP" | "L1GT" | "L1GS" => Some(&[
+                    "B1", "B2", "B3", "B4", "B5", "B6", "B7", "B9", "B10", "B11", "B8",
+                ]),
+                _ => None,
+            }
+        }
+
+        /// Bands of the product at `path`.
+        fn bands(path: &Path) -> Result<(&Path, &str, &'static [&'static str])> {
+            let not_landsat = || GdalEngineError::NotLandsatProduct(path.display().to_string());
+            let (dir, product_id) = Self::product(path).ok_or_else(not_landsat)?;
+            let level = product_id.split('_').nth(1).ok_or_else(not_landsat)?;
+            let bands = Self::level_bands(level).ok_or_else(not_landsat)?;
+            Ok((dir, product_id, bands))
+        }
+    }
+
+    impl Sensor for Landsat {
+        fn detect(&self, path: &Path, _driver_name: Option<&str>) -> bool {
+            Self::bands(path).is_ok()
+        }
+
+        fn band_groups(&self, path: &Path) -> Result<Vec<(String, Indexes)>> {
+            let (dir, product_id, bands) = Self::bands(path)?;
+            bands
+                .iter()
+                .map(|band| {
+                    let band_path = dir.join(format!("{product_id}_{band}.TIF"));
+                    if !band_path.exists() {
+                        return Err(GdalEngineError::ProductFileNotFound(
+                            band_path.display().to_string(),
+                        )
+                        .into());
+                    }
+                    Ok((band_path.display().to_string(), Indexes::from([0usize])))
+                })
+                .collect()
+        }
+
+        fn band_names(&self, path: &Path) -> Option<Vec<String>> {
+            let (_, _, bands) = Self::bands(path).ok()?;
+            Some(bands.iter().map(ToString::to_string).collect())
+        }
+    }
+
+    /// GDAL driver open options, e.g. to decode bands with several threads.
+    ///
+    /// Defaults to no options, opening datasets as [GdalDataset::open] does.
+    #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+    pub struct OpenOptions {
+        options: Vec<(String, String)>,
+    }
+
+    impl OpenOptions {
+        /// Driver open option `key=value`, see the GDAL driver documentation.
+        pub fn option(mut self, key: &str, value: &str) -> Self {
+            self.options.push((key.to_string(), value.to_string()));
+            self
+        }
+
+        /// Decode with `num_threads` threads, or all CPUs if [None],
+        /// for drivers with a `NUM_THREADS` open option, e.g. GTiff.
+        pub fn num_threads(self, num_threads: Option<usize>) -> Self {
+            let value = num_threads.map_or_else(|| String::from("ALL_CPUS"), |n| n.to_string());
+            self.option("NUM_THREADS", &value)
+        }
+
+        /// Options as GDAL `KEY=VALUE` strings.
+        pub(crate) fn to_gdal_options(&self) -> Vec<String> {
+            self.options
+                .iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect()
+        }
+
+        fn open_dataset(&self, path: impl AsRef<Path>) -> Result<GdalDataset> {
+            if self.options.is_empty() {
+                return Ok(GdalDataset::open(path)?);
+            }
+            let gdal_options = self.to_gdal_options();
+            let open_options: Vec<&str> = gdal_options.iter().map(String::as_str).collect();
+            let dataset_options = DatasetOptions {
+                open_options: Some(&open_options),
+                ..Default::default()
+            };
+            Ok(GdalDataset::open_ex(path, dataset_options)?)
+        }
+    }
+
+    /// Builder to open a [GdalFile] with non default options.
     #[derive(Debug)]
-    pub struct GdalFile<T: GdalDataType> {
+    pub struct GdalFileBuilder<T: GdalDataType> {
         _t: PhantomData<T>,
         path: Arc<Path>,
-        dataset: Rc<GdalDataset>,
+        metadata_domains: Vec<String>,
+        block_aligned_reads: bool,
+        parallel_bands: bool,
+        open_options: OpenOptions,
+        block_cache: Option<Arc<BlockCache<T>>>,
     }
 
-    impl<T: GdalDataType> File<T> for GdalFile<T> {
-        fn open(path: impl AsRef<Path>) -> Result<Self> {
-            let dataset = Rc::new(GdalDataset::open(&path)?);
-            Ok(GdalFile {
+    impl<T: GdalDataType> GdalFileBuilder<T> {
+        pub fn new(path: impl AsRef<Path>) -> Self {
+            GdalFileBuilder {
                 path: Arc::from(path.as_ref()),
+                metadata_domains: DEFAULT_METADATA_DOMAINS
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+                block_aligned_reads: true,
+                parallel_bands: false,
+                open_options: OpenOptions::default(),
+                block_cache: None,
+                _t: PhantomData,
+            }
+        }
+
+        /// Read bands by whole native blocks through `cache`,
+        /// which can be shared with other files.
+        pub fn block_cache(mut self, cache: Arc<BlockCache<T>>) -> Self {
+            self.block_cache = Some(cache);
+            self
+        }
+
+        /// Whether band reads are snapped out to whole native blocks
+        /// and cropped, defaults to `true`.
+        ///
+        /// Avoids decompressing blocks shared by adjacent reads more than once,
+        /// e.g. for JPEG2000 Sentinel-2 bands.
+        pub fn block_aligned_reads(mut self, block_aligned_reads: bool) -> Self {
+            self.block_aligned_reads = block_aligned_reads;
+            self
+        }
+
+        /// Whether bands are built in parallel, defaults to `false`.
+        ///
+        /// Each thread opens its own dataset, which may cost more
+        /// than it saves for files with few bands, but pays off
+        /// for many bands with slow metadata, e.g. of Sentinel-2 products.
+        pub fn parallel_bands(mut self, parallel_bands: bool) -> Self {
+            self.parallel_bands = parallel_bands;
+            self
+        }
+
+        /// Open the dataset, and those band reads open, with `options`.
+        pub fn open_options(mut self, options: OpenOptions) -> Self {
+            self.open_options = options;
+            self
+        }
+
+        /// Include metadata from `domains` on top of the default (empty) domain.
+        pub fn metadata_domains(mut self, domains: &[&str]) -> Self {
+            self.metadata_domains
+                .extend(domains.iter().map(ToString::to_string));
+            self
+        }
+
+        pub fn open(self) -> Result<GdalFile<T>> {
+            let dataset = Rc::new(self.open_options.open_dataset(&self.path)?);
+            Ok(GdalFile {
+                modified: source_modified(&self.path),
+                path: self.path,
                 dataset,
+                built_bands: Cell::new(0),
+                metadata_domains: Rc::from(self.metadata_domains),
+                block_aligned_reads: self.block_aligned_reads,
+                parallel_bands: self.parallel_bands,
+                open_options: Arc::new(self.open_options),
+                block_cache: self.block_cache,
                 _t: PhantomData,
             })
         }
+    }
+
+    #[derive(Debug)]
+    pub struct GdalFile<T: GdalDataType> {
+        _t: PhantomData<T>,
+        path: Arc<Path>,
+        /// Last modification time of the source when opened.
+        modified: Option<SystemTime>,
+        dataset: Rc<GdalDataset>,
+        /// Bands built, each holding a reference to `dataset`.
+        built_bands: Cell<usize>,
+        metadata_domains: Rc<[String]>,
+        block_aligned_reads: bool,
+        parallel_bands: bool,
+        open_options: Arc<OpenOptions>,
+        block_cache: Option<Arc<BlockCache<T>>>,
+    }
+
+    impl<T: GdalDataType> File<T> for GdalFile<T> {
+        fn open(path: impl AsRef<Path>) -> Result<Self> {
+            GdalFileBuilder::new(path).open()
+        }
         fn description(&self) -> Result<String> {
             Ok(self.dataset.description()?)
         }
         fn geo_bounds(&self) -> Result<GeoBounds> {
-            let transform = self.transform()?;
-            let top_left_geo = geo::Point::new(transform.xoff(), transform.yoff());
-            let pixel_shape = Point::<f64>::from(try_tuple_cast(self.dataset.raster_size())?);
-            let bottom_right_geo = pixel_shape.affine_transform(&transform);
-            let min = (top_left_geo.x(), bottom_right_geo.y());
-            let max = (bottom_right_geo.x(), top_left_geo.y());
-            let geo_bounds = Rect::new(min, max);
-            Ok(GeoBounds::from(CrsGeometry::new(transform.crs, geo_bounds)))
+            self.transform()?.geo_bounds(self.dataset.raster_size())
         }
 
+        /// Fails with [RusterioError::NonInvertibleTransform]
+        /// if transform is not invertible.
         fn transform(&self) -> Result<ReadGeoTransform> {
-            let gdal_transform = self.dataset.geo_transform()?;
-            Ok(ReadGeoTransform::new(
-                gdal_transform[1],
-                gdal_transform[2],
-                gdal_transform[0],
-                gdal_transform[4],
-                gdal_transform[5],
-                gdal_transform[3],
-                self.crs(),
-            ))
+            let transform = ReadGeoTransform::from_gdal(self.dataset.geo_transform()?, self.crs()?);
+            transform.try_inverse()?;
+            Ok(transform)
         }
         fn num_bands(&self) -> usize {
             self.dataset.raster_count()
         }
-        fn metadata(&self) -> HashMap<String, String> {
-            filter_metadata_gdal(self.dataset.as_ref())
+        fn metadata(&self) -> Metadata {
+            let mut metadata = filter_metadata_gdal(self.dataset.as_ref(), &self.metadata_domains);
+            metadata.extend(subdataset_descriptions(self.dataset.as_ref()));
+            metadata
+        }
+        fn band_block_size(&self, index: usize) -> Result<(usize, usize)> {
+            Ok(self.dataset.rasterband(index + 1)?.block_size())
         }
         fn band(&self, index: usize) -> Result<RasterBand<T>> {
-            let info: Rc<dyn BandInfo> = Rc::new(GdalBandInfo(Rc::clone(&self.dataset), index + 1));
-            let reader: Arc<dyn BandReader<T>> =
-                Arc::new(GdalBandReader(Arc::clone(&self.path), index + 1));
-            Ok(RasterBand { info, reader })
+            let prepared = prepare_band(&self.dataset, index, self.needs_block_size())?;
+            Ok(self.build_band(index, prepared))
+        }
+
+        /// Prepares bands in parallel, each thread opening its own dataset,
+        /// as Sentinel-2 band metadata is slow to fetch.
+        ///
+        /// Only the cheap wrapping of prepared bands into [RasterBand]s,
+        /// which share the non `Send` dataset of the file, runs serially.
+        fn par_bands(&self, indexes: Indexes) -> Result<Box<[RasterBand<T>]>> {
+            if !self.parallel_bands {
+                return self.bands(indexes);
+            }
+            let indexes = self.band_indexes(indexes)?;
+            let (path, open_options) = (&self.path, &self.open_options);
+            let with_block_size = self.needs_block_size();
+            let prepared_bands = indexes
+                .par_iter()
+                .map_init(
+                    || None,
+                    |dataset: &mut Option<GdalDataset>, index| {
+                        let dataset = match dataset {
+                            Some(dataset) => dataset,
+                            None => dataset.insert(open_options.open_dataset(path.as_ref())?),
+                        };
+                        prepare_band(dataset, *index, with_block_size)
+                    },
+                )
+                .collect::<Result<Vec<_>>>()?;
+            Ok(indexes
+                .iter()
+                .zip(prepared_bands)
+                .map(|(index, prepared)| self.build_band(*index, prepared))
+                .collect())
+        }
+    }
+
+    impl<T: GdalDataType> Drop for GdalFile<T> {
+        /// Dataset closes once its last reference drops,
+        /// which may outlive the file if bands still hold it.
+        fn drop(&mut self) {
+            debug!("closing dataset: {}", self.path.display());
+            let unexpected_references = self.unexpected_references();
+            if unexpected_references > 0 {
+                warn!(
+                    "dataset {} still held by {unexpected_references} references besides its bands",
+                    self.path.display()
+                );
+            } else {
+                debug!(
+                    "dataset {} still held by {} bands",
+                    self.path.display(),
+                    Rc::strong_count(&self.dataset) - 1
+                );
+            }
         }
     }
 
     impl<T: GdalDataType> GdalFile<T> {
-        fn crs(&self) -> Rc<Box<str>> {
-            Rc::new(Box::from(self.dataset.projection()))
+        /// References to the dataset other than this file and the bands it built.
+        pub(crate) fn unexpected_references(&self) -> usize {
+            (Rc::strong_count(&self.dataset) - 1).saturating_sub(self.built_bands.get())
+        }
+
+        /// Whether band readers need the native block size.
+        fn needs_block_size(&self) -> bool {
+            self.block_cache.is_some() || self.block_aligned_reads
+        }
+
+        /// Band at `index` with the name and native block size of [prepare_band].
+        fn build_band(
+            &self,
+            index: usize,
+            (name, block_size): (String, Option<(usize, usize)>),
+        ) -> RasterBand<T> {
+            let info: Rc<dyn BandInfo> =
+                Rc::new(GdalBandInfo(Rc::clone(&self.dataset), index + 1, name));
+            self.built_bands.set(self.built_bands.get() + 1);
+            let band_reader = |block_size| GdalBandReader {
+                path: Arc::clone(&self.path),
+                modified: self.modified,
+                index: index + 1,
+                block_size,
+                open_options: Arc::clone(&self.open_options),
+            };
+            let reader: Arc<dyn BandReader<T>> = match (&self.block_cache, block_size) {
+                (Some(cache), Some(block_size)) => Arc::new(CachedBandReader::new(
+                    Arc::new(band_reader(None)),
+                    Arc::clone(cache),
+                    block_size,
+                )),
+                _ => Arc::new(band_reader(block_size)),
+            };
+            RasterBand { info, reader }
+        }
+
+        /// Fails with [RusterioError::NoCrsFound]
+        /// if the dataset has no spatial reference.
+        fn crs(&self) -> Result<Crs> {
+            let projection = self.dataset.projection();
+            if projection.is_empty() {
+                return Err(RusterioError::NoCrsFound {
+                    path: self.path.to_path_buf(),
+                });
+            }
+            Ok(Crs::new(&projection))
         }
     }
 
+    /// Band at index (1 based) of a dataset, with its name.
     #[derive(Debug)]
-    struct GdalBandInfo(Rc<gdal::Dataset>, usize);
+    struct GdalBandInfo(Rc<gdal::Dataset>, usize, String);
+
+    /// Name of band at index (1 based) of `dataset`
+    /// from driver specific metadata.
+    fn band_name(dataset: &GdalDataset, index: usize) -> Result<String> {
+        let band = dataset.rasterband(index)?;
+        match dataset.driver().short_name().as_str() {
+            "SENTINEL2" => filter_metadata_gdal(&band, DEFAULT_METADATA_DOMAINS)
+                .remove("BANDNAME")
+                .filter(|band_name| !band_name.is_empty())
+                .ok_or_else(|| {
+                    GdalEngineError::MetadataKeyNotFound {
+                        object_desc: format!(
+                            "band {index} of {}",
+                            dataset.description().unwrap_or_default()
+                        ),
+                        key: "BANDNAME",
+                    }
+                    .into()
+                }),
+            // Band description, if any.
+            _ => Ok(Some(band.description()?)
+                .filter(|description| !description.is_empty())
+                .unwrap_or_else(|| format!("band_{index}"))),
+        }
+    }
+
+    /// Name of band at index (0 based) of `dataset` for [GdalFile::band],
+    /// and its native block size if `with_block_size`.
+    fn prepare_band(
+        dataset: &GdalDataset,
+        index: usize,
+        with_block_size: bool,
+    ) -> Result<(String, Option<(usize, usize)>)> {
+        let name = band_name(dataset, index + 1)?;
+        let block_size = with_block_size
+            .then(|| Ok::<_, RusterioError>(dataset.rasterband(index + 1)?.block_size()))
+            .transpose()?;
+        Ok((name, block_size))
+    }
 
     impl<'a> BandInfo for GdalBandInfo {
         fn description(&self) -> Result<String> {
@@ -152,79 +883,674 @@ pub mod gdal_engine {
         }
 
         fn name(&self) -> String {
-            match self.0.driver().short_name().as_str() {
-                "SENTINEL2" => return self.metadata().unwrap().remove("BANDNAME").unwrap(),
-                _ => unimplemented!(),
-            };
+            self.2.clone()
+        }
+
+        fn nodata(&self) -> Result<Option<f64>> {
+            Ok(self.0.rasterband(self.1)?.no_data_value())
+        }
+
+        fn size(&self) -> Result<Option<(usize, usize)>> {
+            Ok(Some(self.0.rasterband(self.1)?.size()))
+        }
+
+        fn block_size(&self) -> Result<Option<(usize, usize)>> {
+            Ok(Some(self.0.rasterband(self.1)?.block_size()))
+        }
+
+        fn overview_count(&self) -> Result<usize> {
+            Ok(self.0.rasterband(self.1)?.overview_count()? as usize)
+        }
+
+        fn overview_size(&self, level: usize) -> Result<(usize, usize)> {
+            Ok(self.0.rasterband(self.1)?.overview(level)?.size())
         }
 
         fn metadata(&self) -> Result<Metadata> {
-            Ok(filter_metadata_gdal(&self.0.rasterband(self.1)?))
+            Ok(filter_metadata_gdal(
+                &self.0.rasterband(self.1)?,
+                DEFAULT_METADATA_DOMAINS,
+            ))
         }
     }
 
+    /// Reader of a band of the dataset at `path`.
     #[derive(Debug)]
-    struct GdalBandReader(Arc<Path>, usize);
+    struct GdalBandReader {
+        path: Arc<Path>,
+        /// Last modification time of the source when its file was opened,
+        /// files opened after the source is rewritten read the new source.
+        modified: Option<SystemTime>,
+        /// 1 based band index.
+        index: usize,
+        /// Native (Width, Height) block size to align reads to, if any.
+        block_size: Option<(usize, usize)>,
+        open_options: Arc<OpenOptions>,
+    }
 
-    use self_cell::self_cell;
+    /// Most datasets kept open by [GdalBandReader]s on each thread.
+    const MAX_CACHED_DATASETS: usize = 16;
 
-    self_cell!(
-        struct RasterBandCell {
-            owner: GdalDataset,
+    /// Dataset path, open options and last modification time of its source.
+    type DatasetKey = (Arc<Path>, Arc<OpenOptions>, Option<SystemTime>);
 
-            #[covariant]
-            dependent: GdalRasterBand,
-        }
-    );
+    /// Least recently used datasets of a thread.
+    #[derive(Default)]
+    struct DatasetCache {
+        /// Datasets by key, with the tick of their last use.
+        datasets: HashMap<DatasetKey, (Rc<GdalDataset>, u64)>,
+        tick: u64,
+    }
 
-    fn build_rasterband_cell(path: impl AsRef<Path>, idx: usize) -> Result<RasterBandCell> {
-        // Create owning String on stack.
-        let dataset = GdalDataset::open(path)?;
+    thread_local! {
+        /// Datasets opened by [GdalBandReader]s on this thread.
+        static CACHED_DATASETS: RefCell<DatasetCache> = RefCell::new(DatasetCache::default());
+    }
 
-        // Move String into AstCell, then build Ast inplace.
-        Ok(RasterBandCell::try_new(dataset, |dataset| {
-            dataset.rasterband(idx)
-        })?)
+    /// Dataset of `key` opened on this thread, reused by later reads
+    /// until its source is modified and a file opened on it again.
+    ///
+    /// Datasets aren't [Sync], so each thread keeps its own,
+    /// evicting the least recently used past [MAX_CACHED_DATASETS].
+    fn cached_dataset(key: DatasetKey) -> Result<Rc<GdalDataset>> {
+        CACHED_DATASETS.with_borrow_mut(|cache| {
+            cache.tick += 1;
+            let tick = cache.tick;
+            if let Some((dataset, last_used)) = cache.datasets.get_mut(&key) {
+                *last_used = tick;
+                return Ok(Rc::clone(dataset));
+            }
+            if cache.datasets.len() >= MAX_CACHED_DATASETS {
+                let least_recent = cache
+                    .datasets
+                    .iter()
+                    .min_by_key(|(_, (_, last_used))| *last_used)
+                    .map(|(key, _)| key.clone());
+                if let Some(least_recent) = least_recent {
+                    cache.datasets.remove(&least_recent);
+                }
+            }
+            let (path, options, _) = &key;
+            debug!("opening dataset for reads: {}", path.display());
+            let dataset = Rc::new(options.open_dataset(path.as_ref())?);
+            cache.datasets.insert(key, (Rc::clone(&dataset), tick));
+            Ok(dataset)
+        })
     }
 
     impl GdalBandReader {
-        fn raster_band(&self) -> Result<RasterBandCell> {
-            build_rasterband_cell(self.0.as_ref(), self.1)
+        /// Run `read` on the band of a dataset cached on this thread.
+        fn with_raster_band<R>(
+            &self,
+            read: impl FnOnce(&GdalRasterBand) -> Result<R>,
+        ) -> Result<R> {
+            let dataset = cached_dataset((
+                Arc::clone(&self.path),
+                Arc::clone(&self.open_options),
+                self.modified,
+            ))?;
+            read(&dataset.rasterband(self.index)?)
+        }
+    }
+
+    /// Last modification time of the local file backing a gdal `path`.
+    ///
+    /// Looks through driver prefixes (`SENTINEL2_L2A:...`) and
+    /// virtual file systems (`/vsizip/...`) for an existing file.
+    /// Remote sources yield [None].
+    fn source_modified(path: &Path) -> Option<SystemTime> {
+        path.to_str()?.split(':').find_map(|segment| {
+            let segment = segment
+                .strip_prefix("/vsizip/")
+                .or_else(|| segment.strip_prefix("/vsigzip/"))
+                .or_else(|| segment.strip_prefix("/vsitar/"))
+                .unwrap_or(segment);
+            Path::new(segment).ancestors().find_map(|ancestor| {
+                let metadata = std::fs::metadata(ancestor).ok()?;
+                metadata.is_file().then(|| metadata.modified().ok())?
+            })
+        })
+    }
+
+    impl From<GdalResamplingAlgorithm> for ResampleAlg {
+        fn from(value: GdalResamplingAlgorithm) -> Self {
+            match value {
+                GdalResamplingAlgorithm::NearestNeighbour => ResampleAlg::NearestNeighbour,
+                GdalResamplingAlgorithm::Bilinear => ResampleAlg::Bilinear,
+                GdalResamplingAlgorithm::Cubic => ResampleAlg::Cubic,
+                GdalResamplingAlgorithm::CubicSpline => ResampleAlg::CubicSpline,
+                GdalResamplingAlgorithm::Lanczos => ResampleAlg::Lanczos,
+                GdalResamplingAlgorithm::Average => ResampleAlg::Average,
+                GdalResamplingAlgorithm::Mode => ResampleAlg::Mode,
+                GdalResamplingAlgorithm::Gauss => ResampleAlg::Gauss,
+            }
         }
     }
 
+    fn read_window<T: GdalDataType>(
+        rasterband: &GdalRasterBand,
+        bounds: &ReadBounds,
+        slice: &mut [T],
+    ) -> Result<()> {
+        let window_shape = bounds.shape().x_y();
+        let offset = bounds.min().try_cast()?.x_y();
+        info!("reading at offset: {:?}, shape: {:?}", offset, window_shape);
+        Ok(rasterband.read_into_slice::<T>(offset, window_shape, window_shape, slice, None)?)
+    }
+
     impl<T: GdalDataType> BandReader<T> for GdalBandReader {
+        /// With a block size, reads not aligned to blocks
+        /// read the enclosing blocks, then copy `bounds` out of them.
         fn read_into_slice(&self, bounds: &ReadBounds, slice: &mut [T]) -> Result<()> {
-            let rasterband = self.raster_band()?;
-            let window_shape = bounds.shape().x_y();
-            let offset = bounds.min().try_cast()?.x_y();
-            info!("reading at offset: {:?}, shape: {:?}", offset, window_shape);
-            Ok(rasterband.borrow_dependent().read_into_slice::<T>(
-                offset,
-                window_shape,
-                window_shape,
-                slice,
-                None,
-            )?)
+            self.with_raster_band(|rasterband| {
+                let raster_size = rasterband.size();
+                validate_read_bounds(bounds, raster_size)?;
+                let Some(block_size) = self.block_size else {
+                    return read_window(rasterband, bounds, slice);
+                };
+                let aligned_bounds = block_aligned_bounds(bounds, block_size, raster_size);
+                if aligned_bounds.offset() == bounds.offset()
+                    && aligned_bounds.shape() == bounds.shape()
+                {
+                    return read_window(rasterband, bounds, slice);
+                }
+
+                let mut aligned_buff = vec![T::zero(); aligned_bounds.size()];
+                read_window(rasterband, &aligned_bounds, &mut aligned_buff)?;
+                let (aligned_width, width) = (aligned_bounds.width(), bounds.width());
+                let relative_offset = bounds.offset() - aligned_bounds.offset();
+                slice
+                    .chunks_exact_mut(width)
+                    .zip(relative_offset.y..)
+                    .for_each(|(row, row_idx)| {
+                        let row_start = row_idx * aligned_width + relative_offset.x;
+                        row.copy_from_slice(&aligned_buff[row_start..row_start + width])
+                    });
+                Ok(())
+            })
+        }
+        fn read_into_slice_resampled(
+            &self,
+            bounds: &ReadBounds,
+            out_shape: (usize, usize),
+            slice: &mut [T],
+            algorithm: GdalResamplingAlgorithm,
+        ) -> Result<()> {
+            self.with_raster_band(|rasterband| {
+                validate_read_bounds(bounds, rasterband.size())?;
+                let window_shape = bounds.shape().x_y();
+                let offset = bounds.min().try_cast()?.x_y();
+                info!(
+                    "reading at offset: {:?}, shape: {:?} resampled to {:?} with {:?}",
+                    offset, window_shape, out_shape, algorithm
+                );
+                Ok(rasterband.read_into_slice::<T>(
+                    offset,
+                    window_shape,
+                    out_shape,
+                    slice,
+                    Some(ResampleAlg::from(algorithm)),
+                )?)
+            })
+        }
+        fn read_overview_into_slice(
+            &self,
+            level: usize,
+            bounds: &ReadBounds,
+            slice: &mut [T],
+        ) -> Result<()> {
+            self.with_raster_band(|rasterband| {
+                let overview = rasterband.overview(level)?;
+                validate_read_bounds(bounds, overview.size())?;
+                let window_shape = bounds.shape().x_y();
+                let offset = bounds.min().try_cast()?.x_y();
+                info!(
+                    "reading overview {} at offset: {:?}, shape: {:?}",
+                    level, offset, window_shape
+                );
+                Ok(overview.read_into_slice::<T>(
+                    offset,
+                    window_shape,
+                    window_shape,
+                    slice,
+                    None,
+                )?)
+            })
+        }
+        fn resamples_natively(&self) -> bool {
+            true
+        }
+        fn raster_size(&self) -> Result<(usize, usize)> {
+            self.with_raster_band(|rasterband| Ok(rasterband.size()))
         }
         fn read_to_buffer(&self, bounds: &ReadBounds) -> Result<Buffer<T, 1>> {
             let mut buff = Buffer::new([bounds.size()]);
             self.read_into_slice(bounds, buff.as_mut()).map(|_| buff)
         }
         fn read_pixel(&self, offset: Coord<usize>) -> Result<T> {
-            let rasterband = self.raster_band()?;
-            let window_shape = (1, 1);
-            let offset = offset.try_cast()?.x_y();
             let pixel_buff = &mut [T::zero()];
-            info!("reading pixel at offset: {:?}", offset);
-            rasterband.borrow_dependent().read_into_slice::<T>(
-                offset,
-                window_shape,
-                window_shape,
-                pixel_buff,
-                None,
-            )?;
+            self.read_into_slice(&ReadBounds::new(offset.x_y(), (1, 1)), pixel_buff)?;
             Ok(pixel_buff[0])
         }
+        fn hash_source(&self, mut state: &mut dyn Hasher) {
+            self.path.hash(&mut state);
+            self.index.hash(&mut state);
+            self.modified.hash(&mut state);
+        }
+    }
+}
+
+/// Implementations for in-memory rasters
+pub mod mem_engine {
+
+    use super::*;
+    use crate::Buffer;
+    use geo::Coord;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// Info of a band computed in memory.
+    #[derive(Debug)]
+    pub struct InMemoryBandInfo {
+        pub name: String,
+    }
+
+    impl BandInfo for InMemoryBandInfo {
+        fn name(&self) -> String {
+            self.name.clone()
+        }
+        fn description(&self) -> Result<String> {
+            Ok(self.name.clone())
+        }
+        fn metadata(&self) -> Result<Metadata> {
+            Ok(Metadata::default())
+        }
+        fn nodata(&self) -> Result<Option<f64>> {
+            Ok(None)
+        }
+    }
+
+    /// Band held in memory as row-major data.
+    #[derive(Debug)]
+    pub struct InMemoryBandReader<T: DataType> {
+        data: Box<[T]>,
+        /// (Width, Height)
+        raster_size: (usize, usize),
+        /// Unique per reader, unlike the data address
+        /// which may be reused once the reader is dropped.
+        id: u64,
+    }
+
+    static NEXT_READER_ID: AtomicU64 = AtomicU64::new(0);
+
+    impl<T: DataType> InMemoryBandReader<T> {
+        /// `data` must hold `raster_size` (Width, Height) pixels.
+        pub fn new(data: Vec<T>, raster_size: (usize, usize)) -> Result<Self> {
+            let (width, height) = raster_size;
+            if data.len() != width * height {
+                return Err(RusterioError::ShapeMismatch {
+                    expected: vec![height, width],
+                    found: vec![data.len()],
+                });
+            }
+            Ok(Self {
+                data: data.into_boxed_slice(),
+                raster_size,
+                id: NEXT_READER_ID.fetch_add(1, Ordering::Relaxed),
+            })
+        }
+    }
+
+    impl<T: DataType> BandReader<T> for InMemoryBandReader<T> {
+        fn raster_size(&self) -> Result<(usize, usize)> {
+            Ok(self.raster_size)
+        }
+        fn read_into_slice(&self, bounds: &ReadBounds, slice: &mut [T]) -> Result<()> {
+            validate_read_bounds(bounds, self.raster_size)?;
+            if slice.len() != bounds.size() {
+                return Err(RusterioError::ShapeMismatch {
+                    expected: vec![bounds.size()],
+                    found: vec![slice.len()],
+                });
+            }
+            let (offset, shape) = (bounds.offset(), bounds.shape());
+            if shape.x == 0 {
+                return Ok(());
+            }
+            let raster_width = self.raster_size.0;
+            slice
+                .chunks_exact_mut(shape.x)
+                .zip(offset.y..)
+                .for_each(|(row, row_idx)| {
+                    let row_start = row_idx * raster_width + offset.x;
+                    row.copy_from_slice(&self.data[row_start..row_start + shape.x])
+                });
+            Ok(())
+        }
+        fn read_to_buffer(&self, bounds: &ReadBounds) -> Result<Buffer<T, 1>> {
+            let mut buff = Buffer::new([bounds.size()]);
+            self.read_into_slice(bounds, buff.as_mut()).map(|_| buff)
+        }
+        fn read_pixel(&self, offset: Coord<usize>) -> Result<T> {
+            validate_read_bounds(&ReadBounds::new(offset.x_y(), (1, 1)), self.raster_size)?;
+            Ok(self.data[offset.y * self.raster_size.0 + offset.x])
+        }
+        /// Data is immutable, so the reader id identifies it.
+        fn hash_source(&self, mut state: &mut dyn Hasher) {
+            self.id.hash(&mut state);
+            self.raster_size.hash(&mut state);
+        }
+    }
+
+    /// Raster file held in memory, e.g. for synthetic data or tests.
+    #[derive(Debug)]
+    pub struct MemoryFile<T: DataType> {
+        description: String,
+        /// GDAL ordered geo transform.
+        geo_transform: [f64; 6],
+        crs: Crs,
+        /// (Width, Height)
+        raster_size: (usize, usize),
+        band_names: Box<[String]>,
+        readers: Box<[Arc<InMemoryBandReader<T>>]>,
+    }
+
+    impl<T: DataType> MemoryFile<T> {
+        /// File of `buffer` channels named `band_names`,
+        /// placed by a GDAL ordered `geo_transform` in `crs`.
+        ///
+        /// Fails with [RusterioError::BandCountMismatch]
+        /// if there is not one name per channel.
+        pub fn new(
+            buffer: Buffer<T, 3>,
+            geo_transform: [f64; 6],
+            crs: &str,
+            band_names: &[String],
+        ) -> Result<Self> {
+            let (channels, height, width) = buffer.chw();
+            if band_names.len() != channels {
+                return Err(RusterioError::BandCountMismatch {
+                    expected: channels,
+                    found: band_names.len(),
+                });
+            }
+            let raster_size = (width, height);
+            let (data, _) = buffer.to_owned_parts();
+            let readers = data
+                .chunks_exact((width * height).max(1))
+                .take(channels)
+                .map(|band_data| {
+                    Ok(Arc::new(InMemoryBandReader::new(
+                        band_data.to_vec(),
+                        raster_size,
+                    )?))
+                })
+                .collect::<Result<_>>()?;
+            Ok(Self {
+                description: String::from("in memory"),
+                geo_transform,
+                crs: Crs::new(crs),
+                raster_size,
+                band_names: Box::from(band_names),
+                readers,
+            })
+        }
+
+        /// Like [MemoryFile::new], from a (Channels, Height, Width) array.
+        #[cfg(feature = "ndarray")]
+        pub fn from_ndarray(
+            array: ndarray::Array3<T>,
+            geo_transform: [f64; 6],
+            crs: &str,
+            band_names: &[String],
+        ) -> Result<Self> {
+            let (channels, height, width) = array.dim();
+            let data = array.as_standard_layout().iter().copied().collect();
+            let buffer = Buffer::from_owned_parts(data, [channels, height, width]);
+            Self::new(buffer, geo_transform, crs, band_names)
+        }
+    }
+
+    impl<T: DataType> File<T> for MemoryFile<T> {
+        /// Memory files have no path, so this always fails.
+        fn open(path: impl AsRef<Path>) -> Result<Self> {
+            Err(RusterioError::IoError(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("can't open {} as a memory file", path.as_ref().display()),
+            )))
+        }
+        fn description(&self) -> Result<String> {
+            Ok(self.description.clone())
+        }
+        fn geo_bounds(&self) -> Result<GeoBounds> {
+            self.transform()?.geo_bounds(self.raster_size)
+        }
+        fn transform(&self) -> Result<ReadGeoTransform> {
+            let transform = ReadGeoTransform::from_gdal(self.geo_transform, self.crs.clone());
+            transform.try_inverse()?;
+            Ok(transform)
+        }
+        fn num_bands(&self) -> usize {
+            self.readers.len()
+        }
+        fn band(&self, index: usize) -> Result<RasterBand<T>> {
+            let reader = self.readers.get(index).ok_or_else(|| {
+                RusterioError::BandNotFound(format!("index {index} of {} bands", self.num_bands()))
+            })?;
+            let info: Rc<dyn BandInfo> = Rc::new(InMemoryBandInfo {
+                name: self.band_names[index].clone(),
+            });
+            let reader: Arc<dyn BandReader<T>> = Arc::clone(reader) as _;
+            Ok(RasterBand { info, reader })
+        }
+        fn metadata(&self) -> Metadata {
+            Metadata::default()
+        }
+        /// Bands are held whole, so a block is the whole band.
+        fn band_block_size(&self, _index: usize) -> Result<(usize, usize)> {
+            Ok(self.raster_size)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        block_aligned_bounds,
+        gdal_engine::{Landsat, OpenOptions, Sensor, Sentinel2Level},
+        mem_engine::MemoryFile,
+    };
+    use crate::{
+        components::{
+            bounds::{Bounds, PixelBounds, ReadBounds, ViewBounds},
+            file::File,
+        },
+        Buffer, Indexes, Raster,
+    };
+    use geo::Coord;
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn read_into_strided_ndarray() {
+        use crate::components::{band::BandReader, engines::mem_engine::InMemoryBandReader};
+        use ndarray::{s, Array2};
+
+        let reader = InMemoryBandReader::new((0u16..16).collect(), (4, 4)).unwrap();
+        let bounds = ReadBounds::new((1, 1), (2, 3));
+        // Every other column of a (3, 4) array, non contiguous.
+        let mut array = Array2::zeros((3, 4));
+        reader
+            .read_into_ndarray(&bounds, array.slice_mut(s![.., ..;2]))
+            .unwrap();
+        #[rustfmt::skip]
+        assert_eq!(array.into_raw_vec_and_offset().0, vec![
+            5, 0, 6, 0,
+            9, 0, 10, 0,
+            13, 0, 14, 0,
+        ]);
+        let mut contiguous = Array2::zeros((3, 2));
+        reader
+            .read_into_ndarray(&bounds, contiguous.view_mut())
+            .unwrap();
+        assert_eq!(contiguous.row(2).to_vec(), vec![13, 14]);
+        assert!(reader
+            .read_into_ndarray(&bounds, Array2::zeros((2, 3)).view_mut())
+            .is_err());
+    }
+
+    #[test]
+    fn default_resampled_read_honors_algorithm() {
+        use crate::{
+            components::{band::BandReader, engines::mem_engine::InMemoryBandReader},
+            GdalResamplingAlgorithm,
+        };
+
+        let reader = InMemoryBandReader::new((0u16..16).collect(), (4, 4)).unwrap();
+        let read = |algorithm| {
+            let mut out = vec![0; 4];
+            reader
+                .read_into_slice_resampled(
+                    &ReadBounds::new((0, 0), (4, 4)),
+                    (2, 2),
+                    &mut out,
+                    algorithm,
+                )
+                .unwrap();
+            out
+        };
+        assert_eq!(
+            read(GdalResamplingAlgorithm::NearestNeighbour),
+            [0, 2, 8, 10]
+        );
+        assert_eq!(read(GdalResamplingAlgorithm::Average), [2, 4, 10, 12]);
+    }
+
+    #[test]
+    fn in_memory_readers_hash_apart() {
+        use crate::components::{band::BandReader, engines::mem_engine::InMemoryBandReader};
+        use std::hash::{DefaultHasher, Hasher};
+
+        let source_hash = |reader: &InMemoryBandReader<u16>| {
+            let mut hasher = DefaultHasher::new();
+            reader.hash_source(&mut hasher);
+            hasher.finish()
+        };
+        let reader = InMemoryBandReader::new(vec![0u16; 4], (2, 2)).unwrap();
+        let other = InMemoryBandReader::new(vec![0u16; 4], (2, 2)).unwrap();
+        assert_eq!(source_hash(&reader), source_hash(&reader));
+        assert_ne!(source_hash(&reader), source_hash(&other));
+    }
+
+    #[test]
+    fn block_aligned() {
+        let aligned = block_aligned_bounds(&ReadBounds::new((3, 5), (2, 2)), (4, 4), (10, 6));
+        assert_eq!(aligned.offset(), Coord { x: 0, y: 4 });
+        assert_eq!(aligned.shape(), Coord { x: 8, y: 2 });
+    }
+
+    #[test]
+    fn open_options_to_gdal() {
+        assert!(OpenOptions::default().to_gdal_options().is_empty());
+        let options = OpenOptions::default()
+            .num_threads(Some(4))
+            .option("USE_TILE_AS_BLOCK", "YES");
+        assert_eq!(
+            options.to_gdal_options(),
+            ["NUM_THREADS=4", "USE_TILE_AS_BLOCK=YES"]
+        );
+        assert_eq!(
+            OpenOptions::default().num_threads(None).to_gdal_options(),
+            ["NUM_THREADS=ALL_CPUS"]
+        );
+    }
+
+    #[test]
+    fn sentinel2_level_from_path() {
+        assert_eq!(
+            Sentinel2Level::from_path("S2B_MSIL2A_20241206T093309.SAFE/MTD_MSIL2A.xml"),
+            Some(Sentinel2Level::L2A)
+        );
+        assert_eq!(
+            Sentinel2Level::from_path("S2A_MSIL1C_20241206T093309.SAFE/MTD_MSIL1C.xml"),
+            Some(Sentinel2Level::L1C)
+        );
+        assert_eq!(Sentinel2Level::from_path("image.tif"), None);
+    }
+
+    #[test]
+    fn memory_file_reads_window() {
+        let buffer = Buffer::from_owned_parts((0..36u16).collect(), [3, 3, 4]);
+        let band_names = ["red", "green", "blue"].map(String::from);
+        let file = MemoryFile::new(
+            buffer,
+            [0., 10., 0., 30., 0., -10.],
+            "EPSG:32633",
+            &band_names,
+        )
+        .unwrap();
+        let raster = Raster::from_file(file, Indexes::all()).unwrap();
+        assert_eq!(raster.band_names(), band_names);
+        let buff = raster
+            .view(None, Indexes::all())
+            .unwrap()
+            .clip(ViewBounds::new((1, 1), (2, 2)))
+            .unwrap()
+            .read()
+            .unwrap();
+        assert_eq!(buff.shape(), [3, 2, 2]);
+        assert_eq!(
+            buff.as_ref(),
+            &[5, 6, 9, 10, 17, 18, 21, 22, 29, 30, 33, 34]
+        );
+    }
+
+    #[test]
+    fn landsat_band_groups() {
+        let product_id = "LC09_L2SP_193052_20241206_20241211_02_T1";
+        let dir = std::env::temp_dir().join(product_id);
+        std::fs::create_dir_all(&dir).unwrap();
+        let bands = [
+            "SR_B1", "SR_B2", "SR_B3", "SR_B4", "SR_B5", "SR_B6", "SR_B7", "ST_B10",
+        ];
+        // Band GeoTIFFs holding their band index.
+        for (value, band) in (0u16..).zip(bands) {
+            crate::tests::write_synthetic_geotiff(
+                &dir.join(format!("{product_id}_{band}.TIF")),
+                vec![value; 16],
+            );
+        }
+        let mtl_path = dir.join(format!("{product_id}_MTL.txt"));
+        assert!(Landsat.detect(&dir, None));
+        assert!(Landsat.detect(&mtl_path, None));
+        assert!(!Landsat.detect(&dir.join("image.tif"), Some("GTiff")));
+
+        let opened = super::gdal_engine::open::<u16>(&mtl_path).and_then(|raster| {
+            let buff = raster.view(None, Indexes::all())?.read()?;
+            Ok((raster.band_names(), buff))
+        });
+        std::fs::remove_file(dir.join(format!("{product_id}_ST_B10.TIF"))).unwrap();
+        let missing_band = Landsat.band_groups(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let (band_names, buff) = opened.unwrap();
+        assert_eq!(band_names, bands);
+        assert_eq!(buff.shape(), [bands.len(), 4, 4]);
+        assert!(buff
+            .as_ref()
+            .chunks_exact(16)
+            .zip(0u16..)
+            .all(|(band, value)| band == [value; 16]));
+        assert!(missing_band.is_err());
+    }
+
+    #[test]
+    fn bands_are_expected_dataset_references() {
+        let path = std::env::temp_dir().join("rusterio_dataset_references.tif");
+        crate::tests::write_synthetic_geotiff(&path, vec![0u16; 16]);
+        let references = super::gdal_engine::GdalFile::<u16>::open(&path).and_then(|file| {
+            let bands = file.bands(Indexes::all())?;
+            Ok((bands.len(), file.unexpected_references()))
+        });
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(references.unwrap(), (1, 0));
     }
 }
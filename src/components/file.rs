@@ -1,8 +1,9 @@
-use std::{collections::HashMap, fmt::Debug, path::Path};
+use std::{fmt::Debug, path::Path, rc::Rc};
 
 use crate::{
     components::{
         bounds::GeoBounds, raster::band::RasterBand, transforms::ReadGeoTransform, DataType,
+        Metadata,
     },
     errors::Result,
     indexes::Indexes,
@@ -16,12 +17,29 @@ pub trait File<T: DataType>: Debug + Sized {
     fn transform(&self) -> Result<ReadGeoTransform>;
     fn num_bands(&self) -> usize;
     fn band(&self, index: usize) -> Result<RasterBand<T>>;
+    /// Band indexes selected by `indexes`, resolving band names.
+    fn band_indexes(&self, indexes: Indexes) -> Result<Rc<[usize]>> {
+        Ok(indexes
+            .resolve_names(|| {
+                (0..self.num_bands())
+                    .map(|idx| Ok(self.band(idx)?.info.name()))
+                    .collect()
+            })?
+            .indexes_from(self.num_bands()))
+    }
     fn bands(&self, indexes: Indexes) -> Result<Box<[RasterBand<T>]>> {
-        indexes
-            .indexes_from(self.num_bands())
+        self.band_indexes(indexes)?
             .iter()
             .map(|idx| self.band(*idx))
             .collect()
     }
-    fn metadata(&self) -> HashMap<String, String>;
+    /// Like [File::bands], but may build bands in parallel.
+    ///
+    /// Defaults to [File::bands] for engines where that isn't safe.
+    fn par_bands(&self, indexes: Indexes) -> Result<Box<[RasterBand<T>]>> {
+        self.bands(indexes)
+    }
+    fn metadata(&self) -> Metadata;
+    /// Native (Width, Height) block size of band at `index`.
+    fn band_block_size(&self, index: usize) -> Result<(usize, usize)>;
 }
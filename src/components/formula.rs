@@ -0,0 +1,251 @@
+use crate::errors::{Result, RusterioError};
+
+/// Arithmetic band formula, e.g. `"(B8 - B4) / (B8 + B4)"`.
+///
+/// Supports numbers, band names, `+`, `-`, `*`, `/` and parentheses.
+#[derive(Debug)]
+pub(crate) struct Formula {
+    expr: Expr,
+    /// Band names in order of first use, indexing [Expr::Band].
+    band_names: Vec<String>,
+}
+
+#[derive(Debug)]
+enum Expr {
+    Num(f64),
+    Band(usize),
+    Neg(Box<Expr>),
+    BinOp(Op, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Ident(String),
+    Op(char),
+    Open,
+    Close,
+}
+
+impl Formula {
+    pub fn parse(formula: &str) -> Result<Self> {
+        let tokens = tokenize(formula)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            pos: 0,
+            band_names: Vec::new(),
+        };
+        let expr = parser.expr()?;
+        if parser.pos != tokens.len() {
+            return Err(invalid_formula(format!(
+                "unexpected {:?} in {formula}",
+                tokens[parser.pos]
+            )));
+        }
+        Ok(Self {
+            expr,
+            band_names: parser.band_names,
+        })
+    }
+
+    /// `(lhs - rhs) / (lhs + rhs)` of bands named `lhs` and `rhs`,
+    /// e.g. NDVI of near infrared and red bands.
+    pub fn normalized_difference(lhs: &str, rhs: &str) -> Self {
+        let band = |idx| Box::new(Expr::Band(idx));
+        Self {
+            expr: Expr::BinOp(
+                Op::Div,
+                Box::new(Expr::BinOp(Op::Sub, band(0), band(1))),
+                Box::new(Expr::BinOp(Op::Add, band(0), band(1))),
+            ),
+            band_names: vec![lhs.to_string(), rhs.to_string()],
+        }
+    }
+
+    /// Names of bands used, in order of `band_values` for [Formula::eval].
+    pub fn band_names(&self) -> &[String] {
+        &self.band_names
+    }
+
+    pub fn eval(&self, band_values: &[f64]) -> f64 {
+        self.expr.eval(band_values)
+    }
+}
+
+impl Expr {
+    fn eval(&self, band_values: &[f64]) -> f64 {
+        match self {
+            Expr::Num(val) => *val,
+            Expr::Band(idx) => band_values[*idx],
+            Expr::Neg(expr) => -expr.eval(band_values),
+            Expr::BinOp(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.eval(band_values), rhs.eval(band_values));
+                match op {
+                    Op::Add => lhs + rhs,
+                    Op::Sub => lhs - rhs,
+                    Op::Mul => lhs * rhs,
+                    Op::Div => lhs / rhs,
+                }
+            }
+        }
+    }
+}
+
+fn invalid_formula(reason: String) -> RusterioError {
+    RusterioError::InvalidFormula(reason)
+}
+
+fn tokenize(formula: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = formula.chars().peekable();
+    while let Some(&char) = chars.peek() {
+        match char {
+            char if char.is_whitespace() => {
+                chars.next();
+            }
+            '+' | '-' | '*' | '/' => {
+                tokens.push(Token::Op(char));
+                chars.next();
+            }
+            '(' => {
+                tokens.push(Token::Open);
+                chars.next();
+            }
+            ')' => {
+                tokens.push(Token::Close);
+                chars.next();
+            }
+            char if char.is_ascii_digit() || char == '.' => {
+                let mut num = String::new();
+                while let Some(&char) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+                    num.push(char);
+                    chars.next();
+                }
+                let num = num
+                    .parse()
+                    .map_err(|_| invalid_formula(format!("invalid number {num}")))?;
+                tokens.push(Token::Num(num));
+            }
+            char if char.is_alphabetic() || char == '_' => {
+                let mut ident = String::new();
+                while let Some(&char) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '_') {
+                    ident.push(char);
+                    chars.next();
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            char => return Err(invalid_formula(format!("unexpected character {char}"))),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser over:
+///
+/// ```text
+/// expr   = term (("+" | "-") term)*
+/// term   = factor (("*" | "/") factor)*
+/// factor = "-" factor | number | band | "(" expr ")"
+/// ```
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    band_names: Vec<String>,
+}
+
+impl Parser<'_> {
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn next_op(&mut self, ops: [char; 2]) -> Option<char> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) if ops.contains(op) => {
+                self.pos += 1;
+                Some(*op)
+            }
+            _ => None,
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr> {
+        let mut expr = self.term()?;
+        while let Some(op) = self.next_op(['+', '-']) {
+            let op = if op == '+' { Op::Add } else { Op::Sub };
+            expr = Expr::BinOp(op, Box::new(expr), Box::new(self.term()?));
+        }
+        Ok(expr)
+    }
+
+    fn term(&mut self) -> Result<Expr> {
+        let mut expr = self.factor()?;
+        while let Some(op) = self.next_op(['*', '/']) {
+            let op = if op == '*' { Op::Mul } else { Op::Div };
+            expr = Expr::BinOp(op, Box::new(expr), Box::new(self.factor()?));
+        }
+        Ok(expr)
+    }
+
+    fn factor(&mut self) -> Result<Expr> {
+        match self.next().cloned() {
+            Some(Token::Op('-')) => Ok(Expr::Neg(Box::new(self.factor()?))),
+            Some(Token::Num(val)) => Ok(Expr::Num(val)),
+            Some(Token::Ident(name)) => {
+                let idx = match self.band_names.iter().position(|used| used.eq(&name)) {
+                    Some(idx) => idx,
+                    None => {
+                        self.band_names.push(name);
+                        self.band_names.len() - 1
+                    }
+                };
+                Ok(Expr::Band(idx))
+            }
+            Some(Token::Open) => {
+                let expr = self.expr()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => Err(invalid_formula(String::from("unclosed parenthesis"))),
+                }
+            }
+            token => Err(invalid_formula(format!("unexpected {token:?}"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalized_difference() {
+        let formula = Formula::parse("(B8 - B4) / (B8 + B4)").unwrap();
+        assert_eq!(formula.band_names(), &["B8", "B4"]);
+        assert_eq!(formula.eval(&[3., 1.]), 0.5);
+    }
+
+    #[test]
+    fn precedence_and_negation() {
+        let formula = Formula::parse("-2 * B1 + 1.5 / 3").unwrap();
+        assert_eq!(formula.eval(&[4.]), -7.5);
+    }
+
+    #[test]
+    fn invalid() {
+        for formula in ["(B1 + B2", "B1 +", "B1 $ B2", "B1 B2"] {
+            assert!(matches!(
+                Formula::parse(formula),
+                Err(RusterioError::InvalidFormula(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+/// Key-value metadata of a raster file, group or band.
+#[derive(Debug, Clone, Default)]
+pub struct Metadata {
+    hashmap: HashMap<String, String>,
+}
+
+impl From<HashMap<String, String>> for Metadata {
+    fn from(hashmap: HashMap<String, String>) -> Self {
+        Self { hashmap }
+    }
+}
+
+impl FromIterator<(String, String)> for Metadata {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+        Self {
+            hashmap: HashMap::from_iter(iter),
+        }
+    }
+}
+
+impl Extend<(String, String)> for Metadata {
+    fn extend<I: IntoIterator<Item = (String, String)>>(&mut self, iter: I) {
+        self.hashmap.extend(iter)
+    }
+}
+
+impl Metadata {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.hashmap.get(key).map(String::as_str)
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        self.hashmap.remove(key)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.hashmap.contains_key(key)
+    }
+
+    /// Iterate over (key, value) pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.hashmap
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.hashmap.keys().map(String::as_str)
+    }
+}
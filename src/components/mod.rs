@@ -1,12 +1,39 @@
 pub mod band;
 pub mod bounds;
+pub mod cache;
 pub mod engines;
 pub mod file;
+pub mod formula;
+pub mod metadata;
+pub mod mosaic;
 pub mod raster;
+pub mod resampling;
 pub mod transforms;
 pub mod view;
 
-type Metadata = std::collections::HashMap<String, String>;
+pub use metadata::Metadata;
 
-pub trait DataType: num::Num + From<bool> + Clone + Copy + Send + Sync + std::fmt::Debug {}
+/// Pixel value type.
+///
+/// [PartialOrd] allows comparing values, e.g. to find band min and max.
+pub trait DataType:
+    num::Num
+    + num::NumCast
+    + PartialOrd
+    + From<bool>
+    + Clone
+    + Copy
+    + Send
+    + Sync
+    + std::fmt::Debug
+    + 'static
+{
+}
+impl DataType for u8 {}
+impl DataType for i8 {}
 impl DataType for u16 {}
+impl DataType for i16 {}
+impl DataType for u32 {}
+impl DataType for i32 {}
+impl DataType for f32 {}
+impl DataType for f64 {}
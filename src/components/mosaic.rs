@@ -0,0 +1,234 @@
+use std::{
+    hash::{Hash, Hasher},
+    rc::Rc,
+    sync::Arc,
+};
+
+use geo::Coord;
+use geo_traits::RectTrait;
+
+use crate::{
+    buffer::Buffer,
+    components::{
+        band::{BandInfo, BandReader},
+        bounds::{Bounds, PixelBounds, ReadBounds},
+        engines::validate_read_bounds,
+        resampling::from_interpolated,
+        DataType, Metadata,
+    },
+    errors::Result,
+    intersection::Intersection,
+};
+
+/// How [crate::Raster::mosaic_with] reads pixels covered by several rasters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Pixel of the first raster covering it.
+    #[default]
+    FirstWins,
+    /// Pixel of the last raster covering it.
+    LastWins,
+    /// Mean of the pixels of the rasters covering it.
+    Average,
+    /// Mean of the pixels of the rasters covering it, weighted by their
+    /// distance to the raster edge up to this many pixels,
+    /// fading each raster out linearly across overlaps.
+    ///
+    /// Raster edges on the mosaic edge don't fade.
+    Feather(usize),
+}
+
+/// Band of a raster within a mosaic.
+#[derive(Debug)]
+pub(crate) struct MosaicSource<T: DataType> {
+    pub reader: Arc<dyn BandReader<T>>,
+    /// Mosaic pixels covered by the band.
+    pub coverage: ReadBounds,
+    /// Mosaic pixel of the top left band pixel.
+    pub band_origin: Coord<isize>,
+    /// Band pixels of this value don't cover the mosaic.
+    pub nodata: Option<T>,
+}
+
+/// Reader of a mosaic band, dispatching reads
+/// to the [MosaicSource]s covering them.
+#[derive(Debug)]
+pub(crate) struct MosaicBandReader<T: DataType> {
+    sources: Box<[MosaicSource<T>]>,
+    /// (Width, Height) of the mosaic.
+    raster_size: (usize, usize),
+    blend: BlendMode,
+    /// Value of pixels not covered by any source.
+    fill_value: T,
+}
+
+impl<T: DataType> MosaicBandReader<T> {
+    pub fn new(
+        sources: Box<[MosaicSource<T>]>,
+        raster_size: (usize, usize),
+        blend: BlendMode,
+        fill_value: T,
+    ) -> Self {
+        Self {
+            sources,
+            raster_size,
+            blend,
+            fill_value,
+        }
+    }
+
+    /// Sources from highest to lowest priority.
+    fn sources_by_priority(&self) -> Box<dyn Iterator<Item = &MosaicSource<T>> + '_> {
+        match self.blend {
+            BlendMode::LastWins => Box::new(self.sources.iter().rev()),
+            _ => Box::new(self.sources.iter()),
+        }
+    }
+
+    /// Call `visit` with the source, index in `bounds`, mosaic pixel and value
+    /// of every pixel within `bounds` covered by a source, by source priority.
+    fn for_each_covered_pixel(
+        &self,
+        bounds: &ReadBounds,
+        mut visit: impl FnMut(&MosaicSource<T>, usize, Coord<usize>, T),
+    ) -> Result<()> {
+        let (min, width) = (bounds.min(), bounds.width());
+        for source in self.sources_by_priority() {
+            let Ok(overlap) = bounds.intersection(&source.coverage) else {
+                continue;
+            };
+            if overlap.size() == 0 {
+                continue;
+            }
+            let overlap_min = overlap.min();
+            let band_offset = Coord {
+                x: (overlap_min.x as isize - source.band_origin.x) as usize,
+                y: (overlap_min.y as isize - source.band_origin.y) as usize,
+            };
+            let band_buff = source
+                .reader
+                .read_to_buffer(&ReadBounds::from((band_offset, overlap.shape())))?;
+            band_buff
+                .as_ref()
+                .chunks_exact(overlap.width())
+                .zip(overlap_min.y..)
+                .for_each(|(row, pixel_y)| {
+                    let row_start = (pixel_y - min.y) * width + overlap_min.x - min.x;
+                    row.iter()
+                        .zip(overlap_min.x..)
+                        .zip(row_start..)
+                        .filter(|((val, _), _)| Some(**val) != source.nodata)
+                        .for_each(|((val, pixel_x), idx)| {
+                            visit(source, idx, Coord::from((pixel_x, pixel_y)), *val)
+                        });
+                });
+        }
+        Ok(())
+    }
+
+    /// Weight of the `source` value of mosaic `pixel` in blended reads.
+    fn blend_weight(&self, source: &MosaicSource<T>, pixel: Coord<usize>) -> f64 {
+        match self.blend {
+            BlendMode::Feather(feather_width) if feather_width > 0 => {
+                let (min, max) = (source.coverage.min(), source.coverage.max());
+                let (width, height) = self.raster_size;
+                // (Is within the mosaic, distance) of each edge.
+                let Some(edge_distance) = [
+                    (min.x > 0, pixel.x - min.x),
+                    (max.x < width, max.x - 1 - pixel.x),
+                    (min.y > 0, pixel.y - min.y),
+                    (max.y < height, max.y - 1 - pixel.y),
+                ]
+                .into_iter()
+                .filter_map(|(is_inner, distance)| is_inner.then_some(distance))
+                .min() else {
+                    return 1.;
+                };
+                (edge_distance + 1).min(feather_width) as f64 / feather_width as f64
+            }
+            _ => 1.,
+        }
+    }
+}
+
+impl<T: DataType> BandReader<T> for MosaicBandReader<T> {
+    fn raster_size(&self) -> Result<(usize, usize)> {
+        Ok(self.raster_size)
+    }
+    fn read_into_slice(&self, bounds: &ReadBounds, slice: &mut [T]) -> Result<()> {
+        validate_read_bounds(bounds, self.raster_size)?;
+        slice.fill(self.fill_value);
+        match self.blend {
+            BlendMode::FirstWins | BlendMode::LastWins => {
+                let mut covered = vec![false; slice.len()];
+                self.for_each_covered_pixel(bounds, |_, idx, _, val| {
+                    if !covered[idx] {
+                        slice[idx] = val;
+                        covered[idx] = true;
+                    }
+                })
+            }
+            BlendMode::Average | BlendMode::Feather(_) => {
+                // (Weighted sum, sum of weights) per pixel.
+                let mut weighted = vec![(0., 0.); slice.len()];
+                self.for_each_covered_pixel(bounds, |source, idx, pixel, val| {
+                    let weight = self.blend_weight(source, pixel);
+                    let value: f64 = num::cast(val).unwrap_or(0.);
+                    weighted[idx].0 += value * weight;
+                    weighted[idx].1 += weight;
+                })?;
+                slice
+                    .iter_mut()
+                    .zip(weighted)
+                    .filter(|(_, (_, weight_sum))| *weight_sum > 0.)
+                    .for_each(|(val, (sum, weight_sum))| {
+                        *val = from_interpolated(sum / weight_sum);
+                    });
+                Ok(())
+            }
+        }
+    }
+    fn read_to_buffer(&self, bounds: &ReadBounds) -> Result<Buffer<T, 1>> {
+        let mut buff = Buffer::new([bounds.size()]);
+        self.read_into_slice(bounds, buff.as_mut()).map(|_| buff)
+    }
+    fn read_pixel(&self, offset: Coord<usize>) -> Result<T> {
+        let pixel_buff = &mut [T::zero()];
+        self.read_into_slice(&ReadBounds::new(offset.x_y(), (1, 1)), pixel_buff)?;
+        Ok(pixel_buff[0])
+    }
+    fn hash_source(&self, mut state: &mut dyn Hasher) {
+        for source in self.sources.iter() {
+            source.reader.hash_source(state);
+            state.write_isize(source.band_origin.x);
+            state.write_isize(source.band_origin.y);
+        }
+        self.blend.hash(&mut state);
+    }
+}
+
+/// [BandInfo] of a mosaic band, from the band of its first raster.
+#[derive(Debug)]
+pub(crate) struct MosaicBandInfo {
+    pub info: Rc<dyn BandInfo>,
+    /// (Width, Height) of the mosaic.
+    pub size: (usize, usize),
+}
+
+impl BandInfo for MosaicBandInfo {
+    fn name(&self) -> String {
+        self.info.name()
+    }
+    fn description(&self) -> Result<String> {
+        self.info.description()
+    }
+    fn metadata(&self) -> Result<Metadata> {
+        self.info.metadata()
+    }
+    fn nodata(&self) -> Result<Option<f64>> {
+        self.info.nodata()
+    }
+    fn size(&self) -> Result<Option<(usize, usize)>> {
+        Ok(Some(self.size))
+    }
+}
@@ -30,6 +30,16 @@ impl<T: DataType> RasterBands<T> {
         self.0.iter().flat_map(|group| group.bands.iter())
     }
 
+    /// Mutable iterator over bands.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut RasterBand<T>> {
+        self.0.iter_mut().flat_map(|group| group.bands.iter_mut())
+    }
+
+    /// Total number of bands across groups.
+    pub fn num_bands(&self) -> usize {
+        self.0.iter().map(|group| group.bands.len()).sum()
+    }
+
     /// Iterate over band groups.
     pub fn groups(&self) -> impl Iterator<Item = &RasterGroup<T>> {
         self.0.iter()
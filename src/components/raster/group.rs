@@ -1,7 +1,8 @@
 use std::{fmt::Debug, hash::Hash};
 
-use crate::components::{
-    raster::band::RasterBand, transforms::GeoReadTransform, DataType, Metadata,
+use crate::{
+    components::{raster::band::RasterBand, transforms::GeoReadTransform, DataType, Metadata},
+    errors::Result,
 };
 
 /// Info for [RasterGroup].
@@ -30,8 +31,12 @@ impl PartialEq for &RasterGroupInfo {
 impl Eq for &RasterGroupInfo {}
 
 impl RasterGroupInfo {
-    pub fn resolution(&self) -> (f64, f64) {
-        (self.transform.a(), self.transform.b())
+    /// Pixel (Width, Height) in crs units.
+    ///
+    /// Fails with [crate::errors::RusterioError::NonInvertibleTransform]
+    /// if the group transform is not invertible.
+    pub fn resolution(&self) -> Result<(f64, f64)> {
+        Ok(self.transform.try_inverse()?.pixel_size())
     }
 }
 
@@ -40,3 +45,52 @@ pub struct RasterGroup<T: DataType> {
     pub info: RasterGroupInfo,
     pub bands: Box<[RasterBand<T>]>,
 }
+
+/// Summary of a [RasterGroup] and its bands, without pixel data.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BandGroupSummary {
+    pub description: String,
+    /// Pixel (Width, Height) in crs units.
+    pub resolution_m: (f64, f64),
+    pub crs: String,
+    pub band_count: usize,
+    pub band_names: Vec<String>,
+    pub bands: Vec<BandSummary>,
+}
+
+impl<T: DataType> TryFrom<&RasterGroup<T>> for BandGroupSummary {
+    type Error = crate::errors::RusterioError;
+    fn try_from(value: &RasterGroup<T>) -> Result<Self> {
+        let RasterGroup { info, bands } = value;
+        Ok(BandGroupSummary {
+            description: info.description.clone(),
+            resolution_m: info.resolution()?,
+            crs: info.transform.crs().to_string(),
+            band_count: bands.len(),
+            band_names: bands.iter().map(|band| band.info.name()).collect(),
+            bands: bands
+                .iter()
+                .map(BandSummary::try_from)
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+/// Summary of a band, without pixel data.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BandSummary {
+    pub name: String,
+    pub description: String,
+    pub nodata: Option<f64>,
+}
+
+impl<T: DataType> TryFrom<&RasterBand<T>> for BandSummary {
+    type Error = crate::errors::RusterioError;
+    fn try_from(value: &RasterBand<T>) -> Result<Self> {
+        Ok(BandSummary {
+            name: value.info.name(),
+            description: value.info.description()?,
+            nodata: value.info.nodata()?,
+        })
+    }
+}
@@ -1,25 +1,57 @@
 pub mod band;
 pub mod group;
 
-use log::info;
-use std::{fmt::Debug, path::Path};
+use geo::{AffineOps, Coord, Point, Rect};
+use geo_traits::RectTrait;
+use log::{info, warn};
+use proj::Proj;
+#[cfg(feature = "tokio")]
+use std::pin::Pin;
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    hash::{DefaultHasher, Hasher},
+    path::Path,
+    rc::Rc,
+    sync::Arc,
+};
+#[cfg(feature = "tokio")]
+use tokio_stream::{wrappers::ReceiverStream, Stream};
 
 use crate::{
     components::{
-        bounds::{Bounds, GeoBounds},
+        band::{BandInfo, BandReader, NamedBandInfo},
+        bounds::{Bounds, GeoBounds, PixelBounds, ReadBounds},
+        engines::mem_engine::{InMemoryBandInfo, InMemoryBandReader},
         file::File,
+        formula::Formula,
+        mosaic::{BlendMode, MosaicBandInfo, MosaicBandReader, MosaicSource},
         raster::{
-            band::RasterBands,
-            group::{RasterGroup, RasterGroupInfo},
+            band::{RasterBand, RasterBands},
+            group::{BandGroupSummary, RasterGroup, RasterGroupInfo},
         },
-        view::InfoView,
-        DataType,
+        resampling::ResamplingMethod,
+        transforms::ReadGeoTransform,
+        view::View,
+        DataType, Metadata,
     },
-    errors::Result,
-    intersection::Intersection,
+    crs_geo::{utm_crs, Crs, CrsGeometry, CrsGeometryError},
+    errors::{Result, RusterioError},
+    intersection::{BoundingUnion, Intersection, IntersectionError},
     Indexes,
 };
 
+/// Distance, in pixels, bounds can move when snapped to the pixel grid
+/// without warning (or failing in strict mode).
+const MAX_SNAP_DISTANCE: f64 = 0.5;
+
+/// (Width, Height) of the windows [Raster::sample] batches
+/// reads of bands without a native block size in.
+const SAMPLE_WINDOW_SIZE: (usize, usize) = (256, 256);
+
+/// Distance, in pixels, within which bounds are on the pixel grid.
+const GRID_TOLERANCE: f64 = 1e-6;
+
 /// Collection of [band::RasterBand] that cover [GeoBounds].
 pub struct Raster<T: DataType> {
     /// Bounds of full raster
@@ -47,10 +79,12 @@ impl<T: DataType> Raster<T> {
     }
 
     pub fn new<F: File<T>>(path: impl AsRef<Path>, band_indexes: Indexes) -> Result<Self> {
-        let file = F::open(path)?;
+        Self::from_file(F::open(path)?, band_indexes)
+    }
 
-        let transform = file.transform()?;
-        let transform = transform.inverse();
+    /// Build raster from an already opened [File].
+    pub fn from_file<F: File<T>>(file: F, band_indexes: Indexes) -> Result<Self> {
+        let transform = file.transform()?.try_inverse()?;
         let bounds = file.geo_bounds()?;
         let description = file.description()?;
         let metadata = file.metadata();
@@ -59,7 +93,7 @@ impl<T: DataType> Raster<T> {
             transform,
             metadata,
         };
-        let raster_bands = file.bands(band_indexes)?;
+        let raster_bands = file.par_bands(band_indexes)?;
         let bands = RasterBands::from(RasterGroup {
             info,
             bands: raster_bands,
@@ -70,19 +104,707 @@ impl<T: DataType> Raster<T> {
         Ok(Self::init(bounds, bands))
     }
 
+    /// Stack bands of `rasters` over the intersection of their bounds.
+    ///
+    /// Fails with [RusterioError::NoBandsSelected] if `rasters` is empty,
+    /// [RusterioError::CrsMismatch] if rasters are not all in the same crs,
+    /// see [Raster::stack_reprojected], or [RusterioError::NoIntersection]
+    /// if their bounds don't overlap.
     pub fn stack(rasters: Vec<Raster<T>>) -> Result<Raster<T>> {
+        let stack = Self::stack_with(rasters, |lhs, rhs| lhs.intersection(rhs))?;
+        if stack.bounds.width() <= 0. || stack.bounds.height() <= 0. {
+            return Err(RusterioError::NoIntersection(
+                IntersectionError::NoIntersection,
+            ));
+        }
+        Ok(stack)
+    }
+
+    /// Stack bands of `rasters` over the union of their bounds.
+    ///
+    /// Pixels outside of a band are read as [ReadOptions::fill_value](crate::ReadOptions::fill_value),
+    /// or the band nodata, or zero.
+    /// Fails with [RusterioError::CrsMismatch] if rasters are not all in the same crs,
+    /// or [RusterioError::BoundsNotAligned] if the union is off the pixel grid of a band,
+    /// e.g. for rasters offset by a fraction of a pixel.
+    pub fn stack_union(rasters: Vec<Raster<T>>) -> Result<Raster<T>> {
+        let stack = Self::stack_with(rasters, |lhs, rhs| lhs.bounding_union(rhs))?;
+        let distance = stack
+            .bands
+            .groups()
+            .map(|group| stack.bounds.snap_distance(&group.info.transform))
+            .fold(0., f64::max);
+        if distance > GRID_TOLERANCE {
+            return Err(RusterioError::BoundsNotAligned { distance });
+        }
+        Ok(stack)
+    }
+
+    /// Stack bands of `rasters` over their bounds combined with `combine_bounds`.
+    fn stack_with(
+        rasters: Vec<Raster<T>>,
+        combine_bounds: impl Fn(&GeoBounds, &GeoBounds) -> Result<GeoBounds>,
+    ) -> Result<Raster<T>> {
         let mut stack_iter = rasters
             .into_iter()
             .map(|raster| (raster.bounds, raster.bands));
-        let (mut stack_geo_bounds, mut stack_bands) = stack_iter.next().unwrap();
+        let (mut stack_geo_bounds, mut stack_bands) =
+            stack_iter.next().ok_or(RusterioError::NoBandsSelected)?;
         for (geo_bounds, mut bands) in stack_iter {
-            stack_geo_bounds = stack_geo_bounds.intersection(&geo_bounds)?;
+            if geo_bounds.crs() != stack_geo_bounds.crs() {
+                return Err(RusterioError::CrsMismatch {
+                    expected: stack_geo_bounds.crs().to_string(),
+                    found: geo_bounds.crs().to_string(),
+                });
+            }
+            stack_geo_bounds = combine_bounds(&stack_geo_bounds, &geo_bounds)?;
             stack_bands.append(&mut bands);
         }
         Ok(Self::init(stack_geo_bounds, stack_bands))
     }
 
-    pub fn view(&self, bounds: Option<GeoBounds>, band_indexes: Indexes) -> Result<InfoView<T>> {
+    /// Like [Raster::stack], but first reprojecting rasters
+    /// to the crs of the first one, see [Raster::reproject].
+    pub fn stack_reprojected(rasters: Vec<Raster<T>>) -> Result<Raster<T>> {
+        let Some(crs) = rasters
+            .first()
+            .map(|raster| raster.bounds.crs().to_string())
+        else {
+            return Self::stack(rasters);
+        };
+        let rasters = rasters
+            .into_iter()
+            .map(|raster| {
+                if raster.bounds.crs() == crs.as_str() {
+                    Ok(raster)
+                } else {
+                    raster.reproject(&crs)
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Self::stack(rasters)
+    }
+
+    /// Mosaic `rasters` over the union of their bounds,
+    /// reading pixels covered by several rasters from the first one.
+    ///
+    /// See [Raster::mosaic_with].
+    pub fn mosaic(rasters: Vec<Raster<T>>) -> Result<Raster<T>> {
+        Self::mosaic_with(rasters, BlendMode::default())
+    }
+
+    /// Mosaic `rasters` over the union of their bounds, reading each pixel
+    /// from the rasters covering it as set by `blend`. Band pixels equal
+    /// to the band nodata don't cover the mosaic.
+    ///
+    /// Band info is of the first raster. Only rasters of a single pixel size
+    /// on a common pixel grid are supported: fails with [RusterioError::CrsMismatch],
+    /// [RusterioError::BandCountMismatch], [RusterioError::ResolutionMismatch]
+    /// or [RusterioError::BoundsNotAligned] unless rasters share crs, band count,
+    /// pixel size and pixel grid.
+    pub fn mosaic_with(rasters: Vec<Raster<T>>, blend: BlendMode) -> Result<Raster<T>> {
+        let first = rasters.first().ok_or(RusterioError::NoBandsSelected)?;
+        let num_bands = first.bands.num_bands();
+        let resolution = first
+            .bands
+            .groups()
+            .next()
+            .ok_or(RusterioError::NoBandsSelected)?
+            .info
+            .resolution()?;
+        let mut bounds = first.bounds.clone();
+        for raster in rasters.iter() {
+            if raster.crs() != first.crs() {
+                return Err(RusterioError::CrsMismatch {
+                    expected: first.crs().to_string(),
+                    found: raster.crs().to_string(),
+                });
+            }
+            if raster.bands.num_bands() != num_bands {
+                return Err(RusterioError::BandCountMismatch {
+                    expected: num_bands,
+                    found: raster.bands.num_bands(),
+                });
+            }
+            for group in raster.bands.groups() {
+                let found = group.info.resolution()?;
+                let matches = |lhs: f64, rhs: f64| (lhs - rhs).abs() <= 1e-9 * rhs.abs();
+                if !matches(found.0, resolution.0) || !matches(found.1, resolution.1) {
+                    return Err(RusterioError::ResolutionMismatch {
+                        expected: resolution,
+                        found,
+                    });
+                }
+            }
+            bounds = bounds.bounding_union(&raster.bounds)?;
+        }
+        // Origins of all bands at whole mosaic pixels.
+        let distance = rasters
+            .iter()
+            .flat_map(|raster| raster.bands.groups())
+            .map(|group| bounds.snap_distance(&group.info.transform))
+            .fold(0., f64::max);
+        if distance > GRID_TOLERANCE {
+            return Err(RusterioError::BoundsNotAligned { distance });
+        }
+
+        let raster_size = (
+            (bounds.width() / resolution.0).round() as usize,
+            (bounds.height() / resolution.1).round() as usize,
+        );
+        let (min_x, max_y) = (bounds.min().x, bounds.max().y);
+        // Mosaic pixel at `coord`, possibly outside of the mosaic.
+        let to_pixel = |coord: Coord| Coord {
+            x: ((coord.x - min_x) / resolution.0).round() as isize,
+            y: ((max_y - coord.y) / resolution.1).round() as isize,
+        };
+        let mut band_sources: Vec<Vec<MosaicSource<T>>> =
+            (0..num_bands).map(|_| Vec::new()).collect();
+        for raster in rasters.iter() {
+            let (raster_min, raster_max) = (raster.bounds.min(), raster.bounds.max());
+            let raster_min_pixel = to_pixel(Coord::from((raster_min.x, raster_max.y)));
+            let raster_max_pixel = to_pixel(Coord::from((raster_max.x, raster_min.y)));
+            for ((group_info, band), sources) in raster.bands.group_band().zip(&mut band_sources) {
+                // Band pixel at the mosaic origin.
+                let origin_band_pixel = group_info.transform.apply(Coord::from((min_x, max_y)));
+                let band_origin = Coord {
+                    x: -origin_band_pixel.x.round() as isize,
+                    y: -origin_band_pixel.y.round() as isize,
+                };
+                let band_size = band.reader.raster_size()?;
+                let clamp = |val: isize, max: usize| val.clamp(0, max as isize) as usize;
+                let coverage_min = Coord {
+                    x: clamp(raster_min_pixel.x.max(band_origin.x), raster_size.0),
+                    y: clamp(raster_min_pixel.y.max(band_origin.y), raster_size.1),
+                };
+                let coverage_max = Coord {
+                    x: clamp(
+                        raster_max_pixel.x.min(band_origin.x + band_size.0 as isize),
+                        raster_size.0,
+                    )
+                    .max(coverage_min.x),
+                    y: clamp(
+                        raster_max_pixel.y.min(band_origin.y + band_size.1 as isize),
+                        raster_size.1,
+                    )
+                    .max(coverage_min.y),
+                };
+                sources.push(MosaicSource {
+                    reader: Arc::clone(&band.reader),
+                    coverage: ReadBounds::from((coverage_min, coverage_max - coverage_min)),
+                    band_origin,
+                    nodata: band.info.nodata()?.and_then(num::cast::<f64, T>),
+                });
+            }
+        }
+
+        let bands = first
+            .bands
+            .iter()
+            .zip(band_sources)
+            .map(|(band, sources)| {
+                let fill_value = band
+                    .info
+                    .nodata()?
+                    .and_then(num::cast::<f64, T>)
+                    .unwrap_or_else(T::zero);
+                let reader: Arc<dyn BandReader<T>> = Arc::new(MosaicBandReader::new(
+                    sources.into_boxed_slice(),
+                    raster_size,
+                    blend,
+                    fill_value,
+                ));
+                let info: Rc<dyn BandInfo> = Rc::new(MosaicBandInfo {
+                    info: Rc::clone(&band.info),
+                    size: raster_size,
+                });
+                Ok(RasterBand { info, reader })
+            })
+            .collect::<Result<Box<[RasterBand<T>]>>>()?;
+        let transform = ReadGeoTransform::new(
+            resolution.0,
+            0.,
+            min_x,
+            0.,
+            -resolution.1,
+            max_y,
+            first.bounds.interned_crs().clone(),
+        );
+        let info = RasterGroupInfo {
+            description: format!("mosaic of {} rasters", rasters.len()),
+            transform: transform.try_inverse()?,
+            metadata: Metadata::default(),
+        };
+        Ok(Self::init(
+            bounds,
+            RasterBands::from(RasterGroup { info, bands }),
+        ))
+    }
+
+    /// Override band names, in band order.
+    pub(crate) fn rename_bands(&mut self, names: impl IntoIterator<Item = String>) {
+        self.bands.iter_mut().zip(names).for_each(|(band, name)| {
+            band.info = Rc::new(NamedBandInfo {
+                name,
+                info: Rc::clone(&band.info),
+            })
+        });
+    }
+
+    /// Info of band at `index`, without reading pixel data.
+    pub fn band_info(&self, index: usize) -> Result<&dyn BandInfo> {
+        self.bands
+            .iter()
+            .nth(index)
+            .map(|band| band.info.as_ref())
+            .ok_or_else(|| {
+                RusterioError::BandNotFound(format!(
+                    "index {index} of {} bands",
+                    self.bands.num_bands()
+                ))
+            })
+    }
+
+    /// Pixels per band, (Width * Height) of the full raster view.
+    pub fn num_pixels_per_band(&self) -> usize {
+        let transforms = self.bands.groups().map(|group| &group.info.transform);
+        self.bounds
+            .build_raster_view_bounds(transforms)
+            .map_or(0, |view_bounds| view_bounds.size())
+    }
+
+    /// Pixels across all bands, (Width * Height * Bands) of the full raster view.
+    pub fn num_pixels(&self) -> usize {
+        self.num_pixels_per_band() * self.bands.num_bands()
+    }
+
+    /// Bytes needed to read the full raster view.
+    pub fn estimated_memory_bytes(&self) -> usize {
+        self.num_pixels() * std::mem::size_of::<T>()
+    }
+
+    /// Cheap change detection token.
+    ///
+    /// Hashes the source of each band (path, band index and
+    /// last modification time of local files when opened), in band order.
+    /// Rasters with the same band selection of the same files hash equal.
+    ///
+    /// Remote sources, e.g. `/vsicurl/` paths, hash by path only:
+    /// their HTTP ETags aren't checked, so changes to them go unnoticed.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.bands
+            .iter()
+            .for_each(|band| band.reader.hash_source(&mut hasher));
+        hasher.finish()
+    }
+
+    /// Summary of each band group and its bands, without reading pixel data,
+    /// e.g. to pick bands of a resolution to view.
+    pub fn list_band_groups(&self) -> Result<Vec<BandGroupSummary>> {
+        self.bands
+            .groups()
+            .map(BandGroupSummary::try_from)
+            .collect()
+    }
+
+    /// Downsample by `factor` into an in-memory raster,
+    /// using [ResamplingMethod::NearestNeighbour].
+    pub fn downsample_by_factor(&self, factor: usize) -> Result<Raster<T>> {
+        self.downsample_by_factor_with_resampling(factor, ResamplingMethod::default())
+    }
+
+    /// Downsample by `factor` into an in-memory raster.
+    ///
+    /// Bands are read at the view resolution of the full raster,
+    /// then resampled to `1/factor` of its pixels in each dimension.
+    /// Right and bottom pixels that don't fill a downsampled pixel
+    /// are dropped, cropping the bounds as [View::decimated] does.
+    ///
+    /// Fails with [RusterioError::InvalidFactor]
+    /// if `factor` is zero or larger than the raster.
+    pub fn downsample_by_factor_with_resampling(
+        &self,
+        factor: usize,
+        resampling: ResamplingMethod,
+    ) -> Result<Raster<T>> {
+        let view = self.view(None, Indexes::all())?;
+        let (width, height) = view.bounds_shape();
+        let decimated = view.decimated(factor)?;
+        let raster_size = decimated.bounds_shape();
+        let bounds = decimated.geo_bounds().clone();
+        let buff = view.read()?;
+        let band_data = buff
+            .as_ref()
+            .chunks(width * height)
+            .map(|band_data| resampling.downsample(band_data, (width, height), factor));
+        self.in_memory(
+            band_data,
+            raster_size,
+            bounds,
+            format!("downsampled by {factor}"),
+        )
+    }
+
+    /// Reproject to `crs` with square `pixel_size` pixels in `crs` units,
+    /// into an in-memory raster.
+    ///
+    /// Pixels take the value of the nearest source pixel,
+    /// or outside of the source raster, the band nodata value (zero if unset).
+    /// No other resampling is done, and source nodata pixels are copied as is.
+    ///
+    /// The warp is eager: the whole source view is read into memory,
+    /// along with a 16 byte source index per target pixel,
+    /// so on large rasters first [Raster::downsample_by_factor] or warp a clipped raster.
+    ///
+    /// Fails with [RusterioError::InvalidPixelSize]
+    /// unless `pixel_size` is positive and finite.
+    pub fn warp(&self, crs: &str, pixel_size: f64) -> Result<Raster<T>> {
+        validate_pixel_size(pixel_size)?;
+        let target_rect = self.bounds.projected_rect(crs)?;
+        let raster_size = (
+            (target_rect.width() / pixel_size).ceil() as usize,
+            (target_rect.height() / pixel_size).ceil() as usize,
+        );
+        let target_min = target_rect.min();
+        let target_max = Coord {
+            x: target_min.x + raster_size.0 as f64 * pixel_size,
+            y: target_rect.max().y,
+        };
+        let target_min = Coord {
+            x: target_min.x,
+            y: target_max.y - raster_size.1 as f64 * pixel_size,
+        };
+        let target_bounds = GeoBounds::from(CrsGeometry::new(
+            Crs::new(crs),
+            Rect::new(target_min, target_max),
+        ));
+
+        // Source view pixel of each target pixel center.
+        let view = self.view(None, Indexes::all())?;
+        let (width, height) = view.bounds_shape();
+        let to_source =
+            Proj::new_known_crs(crs, self.bounds.crs(), None).map_err(CrsGeometryError::from)?;
+        let source_pixels = (0..raster_size.1)
+            .flat_map(|row| (0..raster_size.0).map(move |col| (col, row)))
+            .map(|(col, row)| {
+                let target_coord = target_bounds.pixel_coord(
+                    raster_size,
+                    Coord {
+                        x: col as f64 + 0.5,
+                        y: row as f64 + 0.5,
+                    },
+                );
+                let source_coord = to_source
+                    .convert(target_coord)
+                    .map_err(CrsGeometryError::from)?;
+                let Coord { x: col, y: row } = view
+                    .geo_bounds()
+                    .pixel_position(view.bounds_shape(), source_coord);
+                let in_source =
+                    (0. ..width as f64).contains(&col) && (0. ..height as f64).contains(&row);
+                Ok(in_source.then(|| row as usize * width + col as usize))
+            })
+            .collect::<Result<Vec<Option<usize>>>>()?;
+
+        let buff = view.read()?;
+        let fill_values = self
+            .bands
+            .iter()
+            .map(|band| {
+                Ok(band
+                    .info
+                    .nodata()?
+                    .and_then(num::cast::<f64, T>)
+                    .unwrap_or_else(T::zero))
+            })
+            .collect::<Result<Vec<T>>>()?;
+        let band_data =
+            buff.as_ref()
+                .chunks(width * height)
+                .zip(fill_values)
+                .map(|(band_data, fill_value)| {
+                    source_pixels
+                        .iter()
+                        .map(|source_pixel| source_pixel.map_or(fill_value, |idx| band_data[idx]))
+                        .collect()
+                });
+        self.in_memory(
+            band_data,
+            raster_size,
+            target_bounds,
+            format!("warped to {crs}"),
+        )
+    }
+
+    /// Reproject to `crs` into an in-memory raster,
+    /// keeping about as many pixels as the full raster view.
+    ///
+    /// See [Raster::warp], failing the same way if the reprojected
+    /// bounds give no valid pixel size, e.g. past the edge of `crs`.
+    pub fn reproject(&self, crs: &str) -> Result<Raster<T>> {
+        let target_rect = self.bounds.projected_rect(crs)?;
+        let num_pixels = self.num_pixels_per_band() as f64;
+        let pixel_size = (target_rect.width() * target_rect.height() / num_pixels).sqrt();
+        self.warp(crs, pixel_size)
+    }
+
+    /// Reproject to the WGS84 UTM zone of the raster centroid,
+    /// with square `pixel_size_m` pixels in meters.
+    pub fn reproject_to_utm(&self, pixel_size_m: f64) -> Result<Raster<T>> {
+        validate_pixel_size(pixel_size_m)?;
+        let lonlat = self.bounds.projected_geometry("EPSG:4326")?.center();
+        self.warp(&utm_crs(lonlat.x, lonlat.y), pixel_size_m)
+    }
+
+    /// In-memory raster with the band info of `self`
+    /// and `band_data` of `raster_size` (Width, Height) covering `bounds`.
+    fn in_memory(
+        &self,
+        band_data: impl Iterator<Item = Vec<T>>,
+        raster_size: (usize, usize),
+        bounds: GeoBounds,
+        description: String,
+    ) -> Result<Raster<T>> {
+        let bands = self
+            .bands
+            .iter()
+            .map(|band| Rc::clone(&band.info))
+            .zip(band_data);
+        Self::from_in_memory_bands(bands, raster_size, bounds, description)
+    }
+
+    /// Raster of a single group of in-memory `bands`
+    /// of `raster_size` (Width, Height) covering `bounds`.
+    pub(crate) fn from_in_memory_bands(
+        bands: impl Iterator<Item = (Rc<dyn BandInfo>, Vec<T>)>,
+        raster_size: (usize, usize),
+        bounds: GeoBounds,
+        description: String,
+    ) -> Result<Raster<T>> {
+        let bands = bands
+            .map(|(info, data)| {
+                let reader: Arc<dyn BandReader<T>> =
+                    Arc::new(InMemoryBandReader::new(data, raster_size)?);
+                Ok(RasterBand { info, reader })
+            })
+            .collect::<Result<Box<[RasterBand<T>]>>>()?;
+
+        let transform = bounds.read_geo_transform(raster_size);
+        let info = RasterGroupInfo {
+            description,
+            transform: transform.try_inverse()?,
+            metadata: Metadata::default(),
+        };
+        Ok(Self::init(
+            bounds,
+            RasterBands::from(RasterGroup { info, bands }),
+        ))
+    }
+
+    /// Bounds of the full raster, in the raster CRS.
+    pub fn bounds(&self) -> &GeoBounds {
+        &self.bounds
+    }
+
+    /// Crs of the raster, see [GeoBounds::crs].
+    pub fn crs(&self) -> &str {
+        self.bounds.crs()
+    }
+
+    /// GDAL ordered `[xoff, a, b, yoff, d, e]` geo transform
+    /// of the full raster view, see [View::geo_transform].
+    pub fn geo_transform(&self) -> Result<[f64; 6]> {
+        let transforms = self.bands.groups().map(|group| &group.info.transform);
+        let view_bounds = self.bounds.build_raster_view_bounds(transforms)?;
+        Ok(self.bounds.geo_transform(view_bounds.shape().x_y()))
+    }
+
+    /// Names of all bands, in band order.
+    pub fn band_names(&self) -> Vec<String> {
+        self.bands.iter().map(|band| band.info.name()).collect()
+    }
+
+    /// Evaluate an arithmetic `formula` over band names per pixel,
+    /// e.g. `"(B8 - B4) / (B8 + B4)"`, into a single band raster.
+    ///
+    /// Output band is named after `output_bands`,
+    /// which holds at most one name, or the formula if empty.
+    /// Pixels where any band used is nodata are NaN.
+    pub fn apply_formula(&self, formula: &str, output_bands: &[&str]) -> Result<Raster<f64>> {
+        if output_bands.len() > 1 {
+            return Err(RusterioError::BandCountMismatch {
+                expected: 1,
+                found: output_bands.len(),
+            });
+        }
+        let parsed = Formula::parse(formula)?;
+        let band_indexes = Indexes::by_name(parsed.band_names().iter().cloned());
+        let view = self.view(None, band_indexes)?.to_send_sync();
+        let (width, height) = view.bounds_shape();
+        let data = view.eval_formula(&parsed)?.to_owned_parts().0.into_vec();
+
+        let name = output_bands.first().unwrap_or(&formula).to_string();
+        let info: Rc<dyn BandInfo> = Rc::new(InMemoryBandInfo { name });
+        Raster::from_in_memory_bands(
+            std::iter::once((info, data)),
+            (width, height),
+            self.bounds.clone(),
+            format!("formula {formula}"),
+        )
+    }
+
+    /// Stream the full raster as `tile_size` (Width, Height) tiles in row-major order.
+    ///
+    /// Tiles are read on a blocking task, prefetching up to
+    /// `channel_capacity` tiles ahead of the consumer, at least one.
+    /// Must be called within a tokio runtime.
+    #[cfg(feature = "tokio")]
+    pub fn stream_tiles(
+        &self,
+        tile_size: (usize, usize),
+        channel_capacity: usize,
+    ) -> Pin<Box<dyn Stream<Item = Result<crate::Buffer<T, 3>>> + Send>> {
+        let view = match self.view(None, Indexes::all()) {
+            Ok(view) => view.to_send_sync(),
+            Err(err) => return Box::pin(tokio_stream::once(Err(err))),
+        };
+        let tiles_bounds = match view.tile_bounds(tile_size) {
+            Ok(tiles_bounds) => tiles_bounds,
+            Err(err) => return Box::pin(tokio_stream::once(Err(err))),
+        };
+        let (sender, receiver) = tokio::sync::mpsc::channel(channel_capacity.max(1));
+        tokio::task::spawn_blocking(move || {
+            for tile_bounds in tiles_bounds {
+                let tile = view
+                    .clip(tile_bounds)
+                    .and_then(|tile_view| tile_view.read());
+                if sender.blocking_send(tile).is_err() {
+                    // Stream was dropped.
+                    break;
+                }
+            }
+        });
+        Box::pin(ReceiverStream::new(receiver))
+    }
+
+    /// Values of bands at `band_indexes` at each of `points`,
+    /// which are reprojected to the raster crs if needed.
+    ///
+    /// Each point takes the value of the band pixel containing it,
+    /// or outside of the band, the band nodata value (zero if unset).
+    /// Values are in band order for each point.
+    ///
+    /// Points within the same band block are read with a single window read.
+    pub fn sample(
+        &self,
+        points: &[CrsGeometry<Point>],
+        band_indexes: Indexes,
+    ) -> Result<Vec<Vec<T>>> {
+        let band_indexes = band_indexes.resolve_names(|| Ok(self.band_names()))?;
+        band_indexes.validate(self.bands.num_bands())?;
+        let points = points
+            .iter()
+            .map(|point| point.projected_geometry(self.bounds.crs()))
+            .collect::<std::result::Result<Vec<Point>, CrsGeometryError>>()?;
+        // Samples of all points, per band.
+        let band_samples = band_indexes
+            .select_from(self.bands.group_band().collect())
+            .iter()
+            .map(|(group_info, band)| {
+                let fill_value = band
+                    .info
+                    .nodata()?
+                    .and_then(num::cast::<f64, T>)
+                    .unwrap_or_else(T::zero);
+                let (width, height) = band.reader.raster_size()?;
+                let (block_width, block_height) =
+                    band.info.block_size()?.unwrap_or(SAMPLE_WINDOW_SIZE);
+                // (Point index, pixel) of points within the band, per block.
+                let mut block_points = HashMap::<_, Vec<_>>::new();
+                for (point_idx, point) in points.iter().enumerate() {
+                    let pixel = point.affine_transform(&group_info.transform);
+                    let in_band = (0. ..width as f64).contains(&pixel.x())
+                        && (0. ..height as f64).contains(&pixel.y());
+                    if in_band {
+                        let pixel = Coord {
+                            x: pixel.x() as usize,
+                            y: pixel.y() as usize,
+                        };
+                        block_points
+                            .entry((pixel.x / block_width, pixel.y / block_height))
+                            .or_default()
+                            .push((point_idx, pixel));
+                    }
+                }
+                let mut samples = vec![fill_value; points.len()];
+                for pixels in block_points.values() {
+                    // Smallest window holding the block points.
+                    let (min, max) =
+                        pixels
+                            .iter()
+                            .fold((pixels[0].1, pixels[0].1), |(min, max), (_, pixel)| {
+                                (
+                                    Coord {
+                                        x: min.x.min(pixel.x),
+                                        y: min.y.min(pixel.y),
+                                    },
+                                    Coord {
+                                        x: max.x.max(pixel.x),
+                                        y: max.y.max(pixel.y),
+                                    },
+                                )
+                            });
+                    let window_width = max.x - min.x + 1;
+                    let window = band.reader.read_to_buffer(&ReadBounds::new(
+                        min.x_y(),
+                        (window_width, max.y - min.y + 1),
+                    ))?;
+                    for (point_idx, pixel) in pixels {
+                        samples[*point_idx] =
+                            window.as_ref()[(pixel.y - min.y) * window_width + pixel.x - min.x];
+                    }
+                }
+                Ok(samples)
+            })
+            .collect::<Result<Vec<Vec<T>>>>()?;
+        Ok((0..points.len())
+            .map(|point_idx| {
+                band_samples
+                    .iter()
+                    .map(|samples| samples[point_idx])
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// View of bands at `band_indexes` within `bounds`.
+    ///
+    /// Bounds in another crs are reprojected to the raster crs
+    /// when intersected with the raster bounds, see [GeoBounds::with_crs],
+    /// so the view is always in the raster crs.
+    ///
+    /// Bounds not aligned with the pixel grid are snapped to it,
+    /// warning if that moves them by more than half a pixel.
+    pub fn view(&self, bounds: Option<GeoBounds>, band_indexes: Indexes) -> Result<View<T>> {
+        self.build_view(bounds, band_indexes, false)
+    }
+
+    /// Like [Raster::view], but fails with [RusterioError::BoundsNotAligned]
+    /// instead of snapping bounds by more than half a pixel.
+    pub fn view_strict(&self, bounds: Option<GeoBounds>, band_indexes: Indexes) -> Result<View<T>> {
+        self.build_view(bounds, band_indexes, true)
+    }
+
+    fn build_view(
+        &self,
+        bounds: Option<GeoBounds>,
+        band_indexes: Indexes,
+        strict: bool,
+    ) -> Result<View<T>> {
+        let band_indexes = band_indexes.resolve_names(|| Ok(self.band_names()))?;
+        band_indexes.validate(self.bands.num_bands())?;
+
         let mut view_geo_bounds = self.bounds.clone();
         if let Some(geo_bounds) = bounds {
             view_geo_bounds = view_geo_bounds.intersection(&geo_bounds)?
@@ -90,6 +812,392 @@ impl<T: DataType> Raster<T> {
 
         let view_group_info_bands = band_indexes.select_from(self.bands.group_band().collect());
 
-        InfoView::new(view_geo_bounds, view_group_info_bands)
+        let distance = view_group_info_bands
+            .iter()
+            .map(|(group_info, _)| view_geo_bounds.snap_distance(&group_info.transform))
+            .fold(0., f64::max);
+        if distance > MAX_SNAP_DISTANCE {
+            if strict {
+                return Err(RusterioError::BoundsNotAligned { distance });
+            }
+            warn!("snapping bounds by {distance} pixels to the pixel grid");
+        }
+
+        View::new(view_geo_bounds, view_group_info_bands)
+    }
+}
+
+/// Fails with [RusterioError::InvalidPixelSize]
+/// unless `pixel_size` is positive and finite.
+fn validate_pixel_size(pixel_size: f64) -> Result<()> {
+    if pixel_size > 0. && pixel_size.is_finite() {
+        Ok(())
+    } else {
+        Err(RusterioError::InvalidPixelSize(pixel_size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{components::engines::mem_engine::MemoryFile, Buffer, ViewBounds};
+
+    /// Single band 4x4 raster of 10m pixels with top left corner at `origin`.
+    fn raster_at(origin: (f64, f64), crs: &str) -> Raster<u8> {
+        raster_of((4, 4), 10., origin, crs)
+    }
+
+    /// Single band raster of `raster_size` `pixel_size` pixels
+    /// with top left corner at `origin`.
+    fn raster_of(
+        raster_size: (usize, usize),
+        pixel_size: f64,
+        origin: (f64, f64),
+        crs: &str,
+    ) -> Raster<u8> {
+        let (width, height) = raster_size;
+        let file = MemoryFile::new(
+            Buffer::from_owned_parts(vec![1; width * height], [1, height, width]),
+            [origin.0, pixel_size, 0., origin.1, 0., -pixel_size],
+            crs,
+            &[format!("band_{pixel_size}m")],
+        )
+        .unwrap();
+        Raster::from_file(file, Indexes::all()).unwrap()
+    }
+
+    /// Single band 4x4 raster of 10m pixels holding `value`,
+    /// with top left corner at `origin`.
+    fn constant_raster_at(origin: (f64, f64), value: u8) -> Raster<u8> {
+        let file = MemoryFile::new(
+            Buffer::from_owned_parts(vec![value; 16], [1, 4, 4]),
+            [origin.0, 10., 0., origin.1, 0., -10.],
+            "EPSG:32633",
+            &[String::from("band")],
+        )
+        .unwrap();
+        Raster::from_file(file, Indexes::all()).unwrap()
+    }
+
+    #[test]
+    fn downsample_crops_remainder() {
+        let file = MemoryFile::new(
+            Buffer::from_owned_parts((0..15u8).collect(), [1, 3, 5]),
+            [0., 10., 0., 30., 0., -10.],
+            "EPSG:32633",
+            &[String::from("band")],
+        )
+        .unwrap();
+        let raster = Raster::from_file(file, Indexes::all()).unwrap();
+        let downsampled = raster.downsample_by_factor(2).unwrap();
+        // The 5th column and 3rd row don't fill a downsampled pixel.
+        let bounds = downsampled.bounds();
+        assert_eq!(
+            (bounds.min().x_y(), bounds.max().x_y()),
+            ((0., 10.), (40., 30.))
+        );
+        assert_eq!(
+            downsampled.geo_transform().unwrap(),
+            [0., 20., 0., 30., 0., -20.]
+        );
+        let read = downsampled
+            .view(None, Indexes::all())
+            .unwrap()
+            .read()
+            .unwrap();
+        assert_eq!(read.as_ref(), [6, 8]);
+    }
+
+    #[test]
+    fn warp_invalid_pixel_size() {
+        let raster = raster_at((0., 40.), "EPSG:32633");
+        for pixel_size in [0., -10., f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                raster.warp("EPSG:32633", pixel_size),
+                Err(RusterioError::InvalidPixelSize(_))
+            ));
+            assert!(matches!(
+                raster.reproject_to_utm(pixel_size),
+                Err(RusterioError::InvalidPixelSize(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn reproject_past_crs_edge() {
+        // Up to the pole, which web mercator can't reach.
+        let raster = raster_of((4, 4), 2.5, (0., 90.), "EPSG:4326");
+        assert!(raster.reproject("EPSG:3857").is_err());
+    }
+
+    #[test]
+    fn bands_share_interned_crs() {
+        let file = MemoryFile::new(
+            Buffer::from_owned_parts(vec![0u8; 2 * 4], [2, 2, 2]),
+            [0., 10., 0., 20., 0., -10.],
+            "EPSG:32633",
+            &["a".to_string(), "b".to_string()],
+        )
+        .unwrap();
+        let raster = Raster::from_file(file, Indexes::all()).unwrap();
+        let ids: Vec<usize> = raster
+            .bands
+            .group_band()
+            .map(|(info, _)| info.transform.interned_crs().id())
+            .collect();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(ids[0], ids[1]);
+        assert_eq!(ids[0], Crs::new(raster.crs()).id());
+    }
+
+    #[test]
+    fn sample_matches_pixel_read() {
+        let file = MemoryFile::new(
+            Buffer::from_owned_parts((0..32).collect(), [2, 4, 4]),
+            [0., 10., 0., 40., 0., -10.],
+            "EPSG:32633",
+            &["a".to_string(), "b".to_string()],
+        )
+        .unwrap();
+        let raster = Raster::<u8>::from_file(file, Indexes::all()).unwrap();
+        let center = CrsGeometry::new("EPSG:32633", Point::new(25., 15.));
+        let outside = CrsGeometry::new("EPSG:32633", Point::new(-5., 15.));
+        let top_left = CrsGeometry::new("EPSG:32633", Point::new(5., 35.));
+        let bottom_right = CrsGeometry::new("EPSG:32633", Point::new(35., 5.));
+        let samples = raster
+            .sample(
+                &[center, outside, top_left, bottom_right],
+                Indexes::from([1usize, 0]),
+            )
+            .unwrap();
+        let pixel = raster
+            .view(None, Indexes::from([1usize, 0]))
+            .unwrap()
+            .clip(ViewBounds::new((2, 2), (1, 1)))
+            .unwrap()
+            .read()
+            .unwrap();
+        assert_eq!(samples[0], pixel.as_ref());
+        assert_eq!(samples[0], [26, 10]);
+        assert_eq!(samples[1], [0, 0]);
+        // Read within the same window as the center point.
+        assert_eq!(samples[2], [16, 0]);
+        assert_eq!(samples[3], [31, 15]);
+    }
+
+    #[test]
+    fn view_matching_crs_bounds() {
+        let raster = raster_at((0., 40.), "EPSG:32633");
+        let bounds = GeoBounds::from(CrsGeometry::new(
+            "EPSG:32633",
+            Rect::new((10., 10.), (30., 30.)),
+        ));
+        let view = raster.view(Some(bounds), Indexes::all()).unwrap();
+        assert_eq!(view.bounds_shape(), (2, 2));
+        assert_eq!(view.geo_bounds().crs(), "EPSG:32633");
+        assert_eq!(view.geo_bounds().min(), Coord { x: 10., y: 10. });
+    }
+
+    #[test]
+    fn list_band_groups() {
+        let stack = Raster::stack(vec![
+            raster_of((4, 4), 10., (0., 40.), "EPSG:32633"),
+            raster_of((2, 2), 20., (0., 40.), "EPSG:32633"),
+        ])
+        .unwrap();
+        let groups = stack.list_band_groups().unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].resolution_m, (10., 10.));
+        assert_eq!(groups[1].resolution_m, (20., 20.));
+        assert_eq!(groups[1].band_names, ["band_20m"]);
+        assert_eq!(groups[1].bands[0].name, "band_20m");
+        assert_eq!(groups[1].crs, "EPSG:32633");
+    }
+
+    #[test]
+    fn stack_matching_crs() {
+        let stack = Raster::stack(vec![
+            raster_at((0., 40.), "EPSG:32633"),
+            raster_at((20., 60.), "EPSG:32633"),
+        ])
+        .unwrap();
+        assert_eq!(stack.band_names().len(), 2);
+        assert_eq!(stack.bounds().min(), Coord { x: 20., y: 20. });
+        assert_eq!(stack.bounds().max(), Coord { x: 40., y: 40. });
+    }
+
+    #[test]
+    fn crs() {
+        let raster = raster_at((0., 40.), "EPSG:32633");
+        assert_eq!(raster.crs(), "EPSG:32633");
+        assert_eq!(raster.crs(), raster.bounds().crs());
+    }
+
+    #[test]
+    fn stack_mismatching_crs() {
+        let stack = Raster::stack(vec![
+            raster_at((0., 40.), "EPSG:32633"),
+            raster_at((0., 40.), "EPSG:32634"),
+        ]);
+        assert!(matches!(stack, Err(RusterioError::CrsMismatch { .. })));
+    }
+
+    #[test]
+    fn stack_empty() {
+        assert!(matches!(
+            Raster::<u8>::stack(vec![]),
+            Err(RusterioError::NoBandsSelected)
+        ));
+        assert!(matches!(
+            Raster::<u8>::stack_union(vec![]),
+            Err(RusterioError::NoBandsSelected)
+        ));
+    }
+
+    #[test]
+    fn stack_touching_bounds() {
+        let stack = Raster::stack(vec![
+            raster_at((0., 40.), "EPSG:32633"),
+            raster_at((40., 40.), "EPSG:32633"),
+        ]);
+        assert!(matches!(stack, Err(RusterioError::NoIntersection(_))));
+    }
+
+    #[test]
+    fn stack_union_fills_uncovered() {
+        // 40m squares, the second offset by 20m right and up.
+        let stack = Raster::stack_union(vec![
+            raster_at((0., 40.), "EPSG:32633"),
+            raster_at((20., 60.), "EPSG:32633"),
+        ])
+        .unwrap();
+        assert_eq!(stack.num_pixels_per_band(), 36);
+        let read = stack
+            .view(None, Indexes::all())
+            .unwrap()
+            .read_with_options(crate::ReadOptions {
+                fill_value: Some(255.),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(read.shape(), [2, 6, 6]);
+        let covered = |(row, col): (usize, usize)| [row >= 2 && col < 4, row < 4 && col >= 2];
+        for (idx, val) in read.as_ref().iter().enumerate() {
+            let (band, row, col) = (idx / 36, idx / 6 % 6, idx % 6);
+            let expected = if covered((row, col))[band] { 1 } else { 255 };
+            assert_eq!(*val, expected, "band {band} pixel ({row}, {col})");
+        }
+    }
+
+    #[test]
+    fn stack_union_misaligned() {
+        let stack = Raster::stack_union(vec![
+            raster_at((0., 40.), "EPSG:32633"),
+            raster_at((25., 60.), "EPSG:32633"),
+        ]);
+        assert!(matches!(stack, Err(RusterioError::BoundsNotAligned { .. })));
+    }
+
+    #[test]
+    fn mosaic_side_by_side() {
+        let mosaic = Raster::mosaic(vec![
+            constant_raster_at((0., 40.), 1),
+            constant_raster_at((40., 40.), 2),
+        ])
+        .unwrap();
+        let bounds = mosaic.bounds();
+        assert_eq!(
+            (bounds.min().x_y(), bounds.max().x_y()),
+            ((0., 0.), (80., 40.))
+        );
+        let read = mosaic.view(None, Indexes::all()).unwrap().read().unwrap();
+        assert_eq!(read.shape(), [1, 4, 8]);
+        for row in read.as_ref().chunks(8) {
+            assert_eq!(row, [1, 1, 1, 1, 2, 2, 2, 2]);
+        }
+    }
+
+    #[test]
+    fn mosaic_blend_order() {
+        let read_row = |blend| {
+            let mosaic = Raster::mosaic_with(
+                vec![
+                    constant_raster_at((0., 40.), 1),
+                    constant_raster_at((20., 40.), 2),
+                ],
+                blend,
+            )
+            .unwrap();
+            let read = mosaic.view(None, Indexes::all()).unwrap().read().unwrap();
+            read.as_ref()[..6].to_vec()
+        };
+        assert_eq!(read_row(BlendMode::FirstWins), [1, 1, 1, 1, 2, 2]);
+        assert_eq!(read_row(BlendMode::LastWins), [1, 1, 2, 2, 2, 2]);
+    }
+
+    #[test]
+    fn mosaic_blend_average() {
+        let read = |blend| {
+            let mosaic = Raster::mosaic_with(
+                vec![
+                    constant_raster_at((0., 40.), 10),
+                    constant_raster_at((20., 40.), 20),
+                ],
+                blend,
+            )
+            .unwrap();
+            mosaic.view(None, Indexes::all()).unwrap().read().unwrap()
+        };
+        let average = read(BlendMode::Average);
+        for row in average.as_ref().chunks(6) {
+            assert_eq!(row, [10, 10, 15, 15, 20, 20]);
+        }
+        // Each raster fades out towards its edge in the overlap,
+        // also on the top and bottom rows on the mosaic edge.
+        let feathered = read(BlendMode::Feather(2));
+        for row in feathered.as_ref().chunks(6) {
+            assert_eq!(row, [10, 10, 13, 17, 20, 20]);
+        }
+    }
+
+    #[test]
+    fn mosaic_mismatching_resolution() {
+        let mosaic = Raster::mosaic(vec![
+            raster_at((0., 40.), "EPSG:32633"),
+            raster_of((2, 2), 20., (40., 40.), "EPSG:32633"),
+        ]);
+        assert!(matches!(
+            mosaic,
+            Err(RusterioError::ResolutionMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn mosaic_misaligned() {
+        let mosaic = Raster::mosaic(vec![
+            constant_raster_at((0., 40.), 1),
+            constant_raster_at((45., 40.), 2),
+        ]);
+        assert!(matches!(
+            mosaic,
+            Err(RusterioError::BoundsNotAligned { .. })
+        ));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn stream_tiles_without_prefetch() {
+        use tokio_stream::StreamExt;
+        let raster = raster_at((0., 40.), "EPSG:32633");
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let tiles = runtime
+            .block_on(async { raster.stream_tiles((2, 2), 0).collect::<Vec<_>>().await })
+            .into_iter()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(tiles.len(), 4);
+        assert!(tiles.iter().all(|tile| tile.shape() == [1, 2, 2]));
     }
 }
@@ -0,0 +1,345 @@
+use geo::Coord;
+
+use crate::components::DataType;
+
+/// Method to compute a pixel value from the source pixels it covers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResamplingMethod {
+    /// Value of the source pixel at the center of the block.
+    #[default]
+    NearestNeighbour,
+    /// Mean of the source pixels in the block
+    /// (truncated for integer data types).
+    ///
+    /// Interpolates like [ResamplingMethod::NearestNeighbour].
+    Average,
+    /// Linear interpolation between the 2x2 closest source pixels.
+    Bilinear,
+    /// Cubic convolution over the 4x4 closest source pixels,
+    /// clamped to their range.
+    Cubic,
+}
+
+impl ResamplingMethod {
+    /// Downsample row-major `data` of `shape` (Width, Height)
+    /// by `factor` in each dimension.
+    ///
+    /// Source pixels past the last full block are dropped.
+    pub(crate) fn downsample<T: DataType>(
+        self,
+        data: &[T],
+        shape: (usize, usize),
+        factor: usize,
+    ) -> Vec<T> {
+        let (width, height) = shape;
+        let (out_width, out_height) = (width / factor, height / factor);
+        (0..out_height)
+            .flat_map(|out_row| (0..out_width).map(move |out_col| (out_row, out_col)))
+            .map(|(out_row, out_col)| {
+                let (row, col) = (out_row * factor, out_col * factor);
+                match self {
+                    ResamplingMethod::NearestNeighbour => {
+                        data[(row + factor / 2) * width + col + factor / 2]
+                    }
+                    ResamplingMethod::Average => {
+                        let sum: f64 = (row..row + factor)
+                            .flat_map(|row| &data[row * width + col..row * width + col + factor])
+                            .map(|val| val.to_f64().unwrap_or_default())
+                            .sum();
+                        num::cast(sum / (factor * factor) as f64).unwrap_or_else(T::zero)
+                    }
+                    ResamplingMethod::Bilinear | ResamplingMethod::Cubic => {
+                        // Block center in source pixel coordinates.
+                        let center = |start: usize| start as f64 + factor as f64 / 2. - 0.5;
+                        from_interpolated(self.interpolate(data, shape, center(col), center(row)))
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Value of row-major `data` of `shape` (Width, Height)
+    /// at (`x`, `y`), where source pixel centers lie on integer coordinates.
+    ///
+    /// Coordinates outside the source are clamped to its edge.
+    pub(crate) fn interpolate<T: DataType>(
+        self,
+        data: &[T],
+        shape: (usize, usize),
+        x: f64,
+        y: f64,
+    ) -> f64 {
+        let (width, height) = shape;
+        let (x, y) = (
+            x.clamp(0., (width - 1) as f64),
+            y.clamp(0., (height - 1) as f64),
+        );
+        let pixel = |col: isize, row: isize| {
+            let col = col.clamp(0, width as isize - 1) as usize;
+            let row = row.clamp(0, height as isize - 1) as usize;
+            data[row * width + col].to_f64().unwrap_or_default()
+        };
+        let (col, row) = (x.floor() as isize, y.floor() as isize);
+        let (dx, dy) = (x - x.floor(), y - y.floor());
+        match self {
+            ResamplingMethod::NearestNeighbour | ResamplingMethod::Average => {
+                pixel(x.round() as isize, y.round() as isize)
+            }
+            ResamplingMethod::Bilinear => {
+                let top = pixel(col, row) * (1. - dx) + pixel(col + 1, row) * dx;
+                let bottom = pixel(col, row + 1) * (1. - dx) + pixel(col + 1, row + 1) * dx;
+                top * (1. - dy) + bottom * dy
+            }
+            ResamplingMethod::Cubic => {
+                let offsets = [-1, 0, 1, 2];
+                let window = offsets
+                    .map(|row_off| offsets.map(|col_off| pixel(col + col_off, row + row_off)));
+                let (min, max) = window
+                    .iter()
+                    .flatten()
+                    .fold((f64::MAX, f64::MIN), |(min, max), val| {
+                        (min.min(*val), max.max(*val))
+                    });
+                cubic(window.map(|row_vals| cubic(row_vals, dx)), dy).clamp(min, max)
+            }
+        }
+    }
+}
+
+/// Catmull-Rom interpolation at `t` in \[0, 1\] between `vals[1]` and `vals[2]`.
+fn cubic(vals: [f64; 4], t: f64) -> f64 {
+    let [p0, p1, p2, p3] = vals;
+    p1 + 0.5
+        * t
+        * (p2 - p0 + t * (2. * p0 - 5. * p1 + 4. * p2 - p3 + t * (3. * (p1 - p2) + p3 - p0)))
+}
+
+/// Cast an interpolated value, rounding it for integer data types.
+pub(crate) fn from_interpolated<T: DataType>(val: f64) -> T {
+    let is_integer = num::cast::<f64, T>(0.5).and_then(|half| half.to_f64()) == Some(0.);
+    let val = if is_integer { val.round() } else { val };
+    num::cast(val).unwrap_or_else(T::zero)
+}
+
+/// Resampling algorithms of GDAL's native resampler.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GdalResamplingAlgorithm {
+    #[default]
+    NearestNeighbour,
+    Bilinear,
+    Cubic,
+    CubicSpline,
+    Lanczos,
+    Average,
+    Mode,
+    Gauss,
+}
+
+impl GdalResamplingAlgorithm {
+    /// Closest [ResamplingMethod], for engines without a native resampler.
+    pub(crate) fn fallback_method(self) -> ResamplingMethod {
+        match self {
+            GdalResamplingAlgorithm::NearestNeighbour | GdalResamplingAlgorithm::Mode => {
+                ResamplingMethod::NearestNeighbour
+            }
+            GdalResamplingAlgorithm::Bilinear => ResamplingMethod::Bilinear,
+            GdalResamplingAlgorithm::Cubic
+            | GdalResamplingAlgorithm::CubicSpline
+            | GdalResamplingAlgorithm::Lanczos => ResamplingMethod::Cubic,
+            GdalResamplingAlgorithm::Average | GdalResamplingAlgorithm::Gauss => {
+                ResamplingMethod::Average
+            }
+        }
+    }
+}
+
+/// How bands with a resolution other than the view's are read.
+///
+/// Defaults to [ReadStrategy::GdalResample] with nearest neighbour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadStrategy {
+    /// Read at native resolution, then fill view pixels
+    /// with the band pixel covering them.
+    Chunked,
+    /// Let the engine resample into the view shape.
+    ///
+    /// Engines without a native resampler fall back
+    /// to [ReadStrategy::Chunked].
+    GdalResample(GdalResamplingAlgorithm),
+    /// Read at native resolution, then interpolate view pixels
+    /// from the band pixels around their centers.
+    Interpolated(ResamplingMethod),
+}
+
+impl Default for ReadStrategy {
+    fn default() -> Self {
+        ReadStrategy::GdalResample(GdalResamplingAlgorithm::default())
+    }
+}
+
+/// Nearest neighbour resample of row-major `data`
+/// from `shape` to `out_shape` (Width, Height).
+pub(crate) fn resample_nearest<T: Copy>(
+    data: &[T],
+    shape: (usize, usize),
+    out_shape: (usize, usize),
+    out: &mut [T],
+) {
+    let (width, height) = shape;
+    let (out_width, out_height) = out_shape;
+    out.chunks_exact_mut(out_width.max(1))
+        .take(out_height)
+        .enumerate()
+        .for_each(|(out_row, out_row_data)| {
+            let row = out_row * height / out_height;
+            out_row_data
+                .iter_mut()
+                .enumerate()
+                .for_each(|(out_col, val)| *val = data[row * width + out_col * width / out_width]);
+        });
+}
+
+/// Resample the (offset, shape) `window`, in source pixels, of row-major
+/// `data` of `shape` (Width, Height) into `out` of `out_shape` with `method`.
+///
+/// The window may start and end within source pixels,
+/// e.g. of an overview, and each output pixel covers an equal part of it.
+pub(crate) fn resample_window<T: DataType>(
+    data: &[T],
+    shape: (usize, usize),
+    (offset, window_shape): (Coord<f64>, Coord<f64>),
+    out_shape: (usize, usize),
+    out: &mut [T],
+    method: ResamplingMethod,
+) {
+    let (out_width, out_height) = out_shape;
+    // Output pixel shape in source pixels.
+    let scale = Coord {
+        x: window_shape.x / out_width as f64,
+        y: window_shape.y / out_height as f64,
+    };
+    out.chunks_exact_mut(out_width.max(1))
+        .take(out_height)
+        .enumerate()
+        .for_each(|(out_row, out_row_data)| {
+            let y = offset.y + out_row as f64 * scale.y;
+            out_row_data
+                .iter_mut()
+                .enumerate()
+                .for_each(|(out_col, val)| {
+                    let x = offset.x + out_col as f64 * scale.x;
+                    *val = match method {
+                        ResamplingMethod::Average => {
+                            num::cast(area_mean(data, shape, Coord { x, y }, scale))
+                                .unwrap_or_else(T::zero)
+                        }
+                        // Output pixel center, with source pixel centers on integers.
+                        _ => from_interpolated(method.interpolate(
+                            data,
+                            shape,
+                            x + scale.x / 2. - 0.5,
+                            y + scale.y / 2. - 0.5,
+                        )),
+                    }
+                })
+        });
+}
+
+/// Mean of row-major `data` of `shape` (Width, Height) over the area
+/// of `area_shape` at `start`, weighting source pixels by their overlap.
+fn area_mean<T: DataType>(
+    data: &[T],
+    shape: (usize, usize),
+    start: Coord<f64>,
+    area_shape: Coord<f64>,
+) -> f64 {
+    let (width, height) = shape;
+    // (Index, overlap) of source pixels within [start, start + len) of an axis.
+    let overlaps = |start: f64, len: f64, size: usize| {
+        let end = (start + len).min(size as f64);
+        let start = start.max(0.);
+        (start.floor() as usize..(end.ceil() as usize).min(size))
+            .map(move |idx| (idx, end.min(idx as f64 + 1.) - start.max(idx as f64)))
+    };
+    let (sum, weight_sum) = overlaps(start.y, area_shape.y, height)
+        .flat_map(|(row, row_weight)| {
+            overlaps(start.x, area_shape.x, width)
+                .map(move |(col, col_weight)| (row * width + col, row_weight * col_weight))
+        })
+        .fold((0., 0.), |(sum, weight_sum), (idx, weight)| {
+            let val = data[idx].to_f64().unwrap_or_default();
+            (sum + val * weight, weight_sum + weight)
+        });
+    if weight_sum > 0. {
+        sum / weight_sum
+    } else {
+        0.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 4x4 raster with values 0..16 in row-major order.
+    const DATA: [u16; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+
+    #[test]
+    fn downsample_nearest_neighbour() {
+        let data = ResamplingMethod::NearestNeighbour.downsample(&DATA, (4, 4), 2);
+        assert_eq!(data, [5, 7, 13, 15]);
+    }
+
+    #[test]
+    fn downsample_average() {
+        let data = ResamplingMethod::Average.downsample(&DATA, (4, 4), 2);
+        // Means are 2.5, 4.5, 10.5 and 12.5, truncated.
+        assert_eq!(data, [2, 4, 10, 12]);
+    }
+
+    #[test]
+    fn downsample_bilinear() {
+        let data = ResamplingMethod::Bilinear.downsample(&DATA, (4, 4), 2);
+        // Block centers fall between four pixels: 2.5, 4.5, 10.5 and 12.5, rounded.
+        assert_eq!(data, [3, 5, 11, 13]);
+    }
+
+    #[test]
+    fn cubic_reproduces_linear_gradient() {
+        let data: Vec<f64> = DATA.iter().map(|val| *val as f64).collect();
+        let val = ResamplingMethod::Cubic.interpolate(&data, (4, 4), 1.5, 1.25);
+        assert!((val - 6.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn resample_window_average() {
+        let window = (Coord { x: 0., y: 0. }, Coord { x: 4., y: 4. });
+        let resample = |method| {
+            let mut out = vec![0u16; 4];
+            resample_window(&DATA, (4, 4), window, (2, 2), &mut out, method);
+            out
+        };
+        assert_eq!(resample(ResamplingMethod::NearestNeighbour), [5, 7, 13, 15]);
+        assert_eq!(resample(ResamplingMethod::Average), [2, 4, 10, 12]);
+        // Half pixels on the window edges weigh half.
+        let data: Vec<f64> = DATA.iter().map(|val| *val as f64).collect();
+        let mut out = vec![0.; 1];
+        let window = (Coord { x: 0.5, y: 0. }, Coord { x: 2., y: 1. });
+        resample_window(
+            &data,
+            (4, 4),
+            window,
+            (1, 1),
+            &mut out,
+            ResamplingMethod::Average,
+        );
+        assert_eq!(out, [1.]);
+    }
+
+    #[test]
+    fn nearest_upsample() {
+        let mut out = vec![0u16; 6];
+        resample_nearest(&[1, 2], (2, 1), (3, 2), &mut out);
+        assert_eq!(out, vec![1, 1, 2, 1, 1, 2]);
+    }
+}
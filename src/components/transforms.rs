@@ -1,29 +1,54 @@
-use geo::{AffineTransform, Coord};
-use std::rc::Rc;
+use geo::{AffineOps, AffineTransform, Coord, Point, Rect};
 
 use crate::{
     components::bounds::{Bounds, GeoBounds, ViewBounds},
-    CoordUtils,
+    errors::{Result, RusterioError},
+    try_tuple_cast, CoordUtils, Crs, CrsGeometry,
 };
 
 #[derive(Shrinkwrap, Debug)]
 pub struct ReadGeoTransform {
     #[shrinkwrap(main_field)]
     transform: AffineTransform,
-    pub crs: Rc<Box<str>>,
+    pub crs: Crs,
 }
 
 impl ReadGeoTransform {
-    pub fn new(a: f64, b: f64, xoff: f64, d: f64, e: f64, yoff: f64, crs: Rc<Box<str>>) -> Self {
+    pub fn new(a: f64, b: f64, xoff: f64, d: f64, e: f64, yoff: f64, crs: Crs) -> Self {
         let transform = AffineTransform::new(a, b, xoff, d, e, yoff);
         Self { transform, crs }
     }
 
-    pub fn inverse(&self) -> GeoReadTransform {
-        GeoReadTransform {
-            transform: self.transform.inverse().unwrap(),
-            crs: Rc::clone(&self.crs),
-        }
+    /// Transform of a GDAL ordered `[xoff, a, b, yoff, d, e]` geo transform.
+    pub fn from_gdal(geo_transform: [f64; 6], crs: Crs) -> Self {
+        let [xoff, a, b, yoff, d, e] = geo_transform;
+        Self::new(a, b, xoff, d, e, yoff, crs)
+    }
+
+    /// [GeoBounds] between the top left and bottom right corners
+    /// of a raster of `raster_size` (Width, Height).
+    pub fn geo_bounds(&self, raster_size: (usize, usize)) -> Result<GeoBounds> {
+        let top_left = Point::new(self.xoff(), self.yoff());
+        let bottom_right = Point::<f64>::from(try_tuple_cast(raster_size)?).affine_transform(self);
+        Ok(GeoBounds::from(CrsGeometry::new(
+            self.crs.clone(),
+            Rect::new(top_left.0, bottom_right.0),
+        )))
+    }
+
+    /// Fails with [RusterioError::NonInvertibleTransform]
+    /// if transform is not invertible, e.g. has zero pixel size.
+    pub fn try_inverse(&self) -> Result<GeoReadTransform> {
+        Ok(GeoReadTransform {
+            transform: try_inverse(&self.transform)?,
+            crs: self.crs.clone(),
+        })
+    }
+
+    /// Pixel (Width, Height) in crs units,
+    /// the lengths of the transformed pixel sides.
+    pub fn pixel_size(&self) -> (f64, f64) {
+        (self.a().hypot(self.d()), self.b().hypot(self.e()))
     }
 }
 
@@ -33,15 +58,26 @@ impl ReadGeoTransform {
 pub struct GeoReadTransform {
     #[shrinkwrap(main_field)]
     transform: AffineTransform,
-    crs: Rc<Box<str>>,
+    crs: Crs,
 }
 
 impl GeoReadTransform {
-    pub fn inverse(&self) -> ReadGeoTransform {
-        ReadGeoTransform {
-            transform: self.transform.inverse().unwrap(),
-            crs: Rc::clone(&self.crs),
-        }
+    pub fn crs(&self) -> &str {
+        self.crs.as_wkt()
+    }
+
+    /// Interned [Crs], shared by transforms of the same crs.
+    pub fn interned_crs(&self) -> &Crs {
+        &self.crs
+    }
+
+    /// Fails with [RusterioError::NonInvertibleTransform]
+    /// if transform is not invertible.
+    pub fn try_inverse(&self) -> Result<ReadGeoTransform> {
+        Ok(ReadGeoTransform {
+            transform: try_inverse(&self.transform)?,
+            crs: self.crs.clone(),
+        })
     }
 }
 
@@ -53,8 +89,8 @@ impl ViewReadTransform {
         view_bounds: &ViewBounds,
         geo_bounds: &GeoBounds,
         geo_read_transform: &GeoReadTransform,
-    ) -> Self {
-        let view_pixel_shape: (f64, f64) = view_bounds.shape().try_cast().unwrap().x_y();
+    ) -> Result<Self> {
+        let view_pixel_shape: (f64, f64) = view_bounds.shape().try_cast()?.x_y();
         let view_geo_transform = AffineTransform::new(
             geo_bounds.width() / view_pixel_shape.0,
             0.,
@@ -63,7 +99,16 @@ impl ViewReadTransform {
             -geo_bounds.height() / view_pixel_shape.1,
             geo_bounds.min().y + geo_bounds.height(),
         );
-        Self(view_geo_transform.compose(geo_read_transform))
+        Ok(Self(view_geo_transform.compose(geo_read_transform)))
+    }
+
+    /// Transform from pixels of a view decimated by `factor`,
+    /// whose origin is at `offset` pixels of this transform's view.
+    pub fn decimated(&self, offset: Coord<usize>, factor: usize) -> Self {
+        let factor = factor as f64;
+        let decimation =
+            AffineTransform::new(factor, 0., offset.x as f64, 0., factor, offset.y as f64);
+        Self(decimation.compose(self))
     }
 
     /// Ratio of View to Read shapes. (Height, Width)
@@ -78,11 +123,79 @@ impl ViewReadTransform {
     ///
     /// A.k.a the shape of the chunk of pixels in [ViewBounds] a pixel in [ReadBounds] fills up.
     ///
-    pub fn ratio(&self) -> Coord<usize> {
-        let inv = self.inverse().unwrap();
-        Coord {
+    pub fn ratio(&self) -> Result<Coord<usize>> {
+        let inv = try_inverse(self)?;
+        Ok(Coord {
             x: inv.a().abs() as usize,
             y: inv.e().abs() as usize,
-        }
+        })
+    }
+}
+
+fn try_inverse(transform: &AffineTransform) -> Result<AffineTransform> {
+    transform
+        .inverse()
+        .ok_or(RusterioError::NonInvertibleTransform)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn singular_transform() {
+        let crs = Crs::new("EPSG:32633");
+        let transform = ReadGeoTransform::new(10., 0., 300000., 0., -10., 1300040., crs);
+        assert!(transform.try_inverse().is_ok());
+        let crs = Crs::new("EPSG:32633");
+        let ungeoreferenced = ReadGeoTransform::new(0., 0., 0., 0., 0., 0., crs);
+        assert!(matches!(
+            ungeoreferenced.try_inverse(),
+            Err(RusterioError::NonInvertibleTransform)
+        ));
+    }
+
+    #[test]
+    fn rotated_pixel_size() {
+        let crs = Crs::new("EPSG:32633");
+        // 10x20 pixels rotated by 30 degrees.
+        let (sin, cos) = 30f64.to_radians().sin_cos();
+        let transform = ReadGeoTransform::new(
+            10. * cos,
+            20. * sin,
+            300000.,
+            10. * sin,
+            -20. * cos,
+            1300040.,
+            crs,
+        );
+        let (width, height) = transform
+            .try_inverse()
+            .unwrap()
+            .try_inverse()
+            .unwrap()
+            .pixel_size();
+        assert!((width - 10.).abs() < 1e-9);
+        assert!((height - 20.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn zero_scale_view_transform() {
+        let crs = Crs::new("EPSG:32633");
+        let transform = ReadGeoTransform::new(10., 0., 0., 0., -10., 40., crs)
+            .try_inverse()
+            .unwrap();
+        // Zero width bounds give a zero x scale.
+        let geo_bounds = GeoBounds::from(crate::crs_geo::CrsGeometry::new(
+            Crs::new("EPSG:32633"),
+            geo::Rect::new((0., 0.), (0., 40.)),
+        ));
+        let view_bounds = ViewBounds::new((0, 0), (4, 4));
+        let view_read_transform =
+            ViewReadTransform::new(&view_bounds, &geo_bounds, &transform).unwrap();
+        assert!(matches!(
+            view_read_transform.ratio(),
+            Err(RusterioError::NonInvertibleTransform)
+        ));
     }
 }
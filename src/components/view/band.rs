@@ -1,44 +1,94 @@
 use std::{fmt::Debug, rc::Rc, sync::Arc};
 
-use crate::components::{
-    band::{BandInfo, BandReader},
-    raster::band::RasterBand,
-    transforms::ViewReadTransform,
-    DataType,
+use crate::{
+    components::{
+        band::{BandInfo, BandReader},
+        raster::band::RasterBand,
+        transforms::ViewReadTransform,
+        DataType,
+    },
+    errors::Result,
 };
 
+/// Band info captured when building a [ViewBand],
+/// which unlike [BandInfo] can be shared across threads.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BandSnapshot {
+    pub name: String,
+    pub description: String,
+    pub nodata: Option<f64>,
+    /// Native (Width, Height) of the band.
+    pub size: (usize, usize),
+}
+
 #[derive(Debug, Clone)]
 pub struct ViewBand<T: DataType> {
     pub info: Rc<dyn BandInfo>,
     /// Transform from [RasterView] pixel space to band pixel space.
     pub transform: ViewReadTransform,
     pub reader: Arc<dyn BandReader<T>>,
+    pub snapshot: Arc<BandSnapshot>,
+    /// (Width, Height) of each overview, from finest.
+    pub overviews: Arc<[(usize, usize)]>,
 }
 
-impl<T: DataType> From<(ViewReadTransform, &RasterBand<T>)> for ViewBand<T> {
-    fn from(value: (ViewReadTransform, &RasterBand<T>)) -> Self {
-        let (transform, RasterBand { info, reader }) = value;
-        ViewBand {
+impl<T: DataType> ViewBand<T> {
+    pub fn new(transform: ViewReadTransform, raster_band: &RasterBand<T>) -> Result<Self> {
+        let RasterBand { info, reader } = raster_band;
+        let overviews = (0..info.overview_count()?)
+            .map(|level| info.overview_size(level))
+            .collect::<Result<Arc<[(usize, usize)]>>>()?;
+        let snapshot = Arc::new(BandSnapshot {
+            name: info.name(),
+            description: info.description()?,
+            nodata: info.nodata()?,
+            size: match info.size()? {
+                Some(size) => size,
+                None => reader.raster_size()?,
+            },
+        });
+        Ok(ViewBand {
             transform,
             info: Rc::clone(info),
             reader: Arc::clone(reader),
-        }
+            snapshot,
+            overviews,
+        })
+    }
+
+    pub fn nodata(&self) -> Option<f64> {
+        self.snapshot.nodata
     }
 }
 
+#[derive(Clone)]
 pub struct ReadBand<T: DataType> {
     pub transform: ViewReadTransform,
     pub reader: Arc<dyn BandReader<T>>,
+    pub snapshot: Arc<BandSnapshot>,
+    pub overviews: Arc<[(usize, usize)]>,
+}
+
+impl<T: DataType> ReadBand<T> {
+    pub fn nodata(&self) -> Option<f64> {
+        self.snapshot.nodata
+    }
 }
 
 impl<T: DataType> From<&ViewBand<T>> for ReadBand<T> {
     fn from(value: &ViewBand<T>) -> Self {
         let ViewBand {
-            transform, reader, ..
+            transform,
+            reader,
+            snapshot,
+            overviews,
+            ..
         } = value;
         ReadBand {
             transform: *transform,
             reader: Arc::clone(reader),
+            snapshot: Arc::clone(snapshot),
+            overviews: Arc::clone(overviews),
         }
     }
 }
@@ -1,9 +1,12 @@
 use crate::{
-    components::bounds::{Bounds, ReadBounds, ViewBounds},
+    components::{
+        bounds::{Bounds, PixelBounds, ReadBounds, ViewBounds},
+        resampling::{from_interpolated, ResamplingMethod},
+    },
     errors::Result,
     CoordUtils, DataType,
 };
-use geo::{Coord, MapCoords};
+use geo::Coord;
 use num::Zero;
 use std::ops::Rem;
 
@@ -12,6 +15,7 @@ pub struct ResolutionChunker {
     left_block_width: usize,
     top_block_height: usize,
     view_width: usize,
+    view_height: usize,
     read_shape: Coord<usize>,
 }
 
@@ -21,22 +25,19 @@ impl ResolutionChunker {
             .shape()
             .operate(&read_bounds.shape(), usize::div_ceil); //read_band.transform.ratio();
 
-        let relative_bounds = view_bounds.map_coords(|coord| coord.operate(&ratio, usize::rem));
-        let relative_top_height = relative_bounds.max().y;
-        let top_block_height = if relative_top_height.is_zero() {
-            ratio.y
-        } else {
-            relative_top_height
-        };
-        let left_block_width = ratio.x - relative_bounds.min().x;
+        let relative_offset = view_bounds.offset().operate(&ratio, usize::rem);
+        let top_block_height = ratio.y - relative_offset.y;
+        let left_block_width = ratio.x - relative_offset.x;
 
         let view_width = view_bounds.width();
+        let view_height = view_bounds.height();
         let read_shape = read_bounds.shape();
         Self {
             ratio,
             left_block_width,
             top_block_height,
             view_width,
+            view_height,
             read_shape,
         }
     }
@@ -48,12 +49,16 @@ impl ResolutionChunker {
     ) -> Result<()> {
         for row_idx in 0..self.read_shape.y {
             let block_height = self.read_row_idx_to_block_height(row_idx);
-            let row_start =
-                (row_idx * self.ratio.y + self.top_block_height - block_height) * self.view_width;
+            let block_row = row_idx * self.ratio.y + self.top_block_height - block_height;
+            // Bottom block may be cropped by the view.
+            let block_height = block_height.min(self.view_height - block_row);
+            let row_start = block_row * self.view_width;
             let read_slice = read_buff.as_ref();
             for col_idx in 0..self.read_shape.x {
                 let block_width = self.read_col_idx_to_block_width(col_idx);
                 let col_start = col_idx * self.ratio.x + self.left_block_width - block_width;
+                // Right block may be cropped by the view.
+                let block_width = block_width.min(self.view_width - col_start);
                 let band_write_range = row_start + col_start..row_start + col_start + block_width;
                 band_buff[band_write_range].fill(read_slice[self.read_shape.x * row_idx + col_idx]);
             }
@@ -70,6 +75,38 @@ impl ResolutionChunker {
         Ok(())
     }
 
+    /// Like [ResolutionChunker::read_resolution_chucked],
+    /// but interpolating view pixels with `method`.
+    pub fn read_resolution_interpolated<T: DataType>(
+        self,
+        read_buff: &[T],
+        band_buff: &mut [T],
+        method: ResamplingMethod,
+    ) -> Result<()> {
+        // View pixel center in read pixel coordinates,
+        // with read pixel centers on integers.
+        let to_read = |view_idx: usize, block_start: usize, ratio: usize| {
+            ((view_idx + ratio - block_start) as f64 + 0.5) / ratio as f64 - 0.5
+        };
+        band_buff
+            .chunks_exact_mut(self.view_width)
+            .take(self.view_height)
+            .enumerate()
+            .for_each(|(row_idx, row)| {
+                let y = to_read(row_idx, self.top_block_height, self.ratio.y);
+                row.iter_mut().enumerate().for_each(|(col_idx, val)| {
+                    let x = to_read(col_idx, self.left_block_width, self.ratio.x);
+                    *val = from_interpolated(method.interpolate(
+                        read_buff,
+                        self.read_shape.x_y(),
+                        x,
+                        y,
+                    ));
+                })
+            });
+        Ok(())
+    }
+
     fn read_row_idx_to_block_height(&self, row_idx: usize) -> usize {
         if row_idx.is_zero() {
             self.top_block_height
@@ -86,3 +123,104 @@ impl ResolutionChunker {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{band::BandReader, engines::mem_engine::InMemoryBandReader};
+
+    /// Read `read_bounds` from a band of `raster_size` holding `0..`
+    /// and upsample it into `view_bounds`.
+    fn read_chunked(
+        raster_size: (usize, usize),
+        view_bounds: ViewBounds,
+        read_bounds: ReadBounds,
+    ) -> Vec<u16> {
+        let data = (0..(raster_size.0 * raster_size.1) as u16).collect();
+        let reader = InMemoryBandReader::new(data, raster_size).unwrap();
+        let read_buff = reader.read_to_buffer(&read_bounds).unwrap();
+        let mut band_buff = vec![0; view_bounds.size()];
+        ResolutionChunker::new(&view_bounds, &read_bounds)
+            .read_resolution_chucked(read_buff.as_ref(), &mut band_buff)
+            .unwrap();
+        band_buff
+    }
+
+    #[test]
+    fn same_resolution() {
+        let band_buff = read_chunked(
+            (3, 2),
+            ViewBounds::new((0, 0), (3, 2)),
+            ReadBounds::new((0, 0), (3, 2)),
+        );
+        assert_eq!(band_buff, vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn double_resolution() {
+        let band_buff = read_chunked(
+            (2, 2),
+            ViewBounds::new((0, 0), (4, 4)),
+            ReadBounds::new((0, 0), (2, 2)),
+        );
+        #[rustfmt::skip]
+        assert_eq!(band_buff, vec![
+            0, 0, 1, 1,
+            0, 0, 1, 1,
+            2, 2, 3, 3,
+            2, 2, 3, 3,
+        ]);
+    }
+
+    #[test]
+    fn triple_resolution_with_offset() {
+        let band_buff = read_chunked(
+            (2, 2),
+            ViewBounds::new((1, 1), (5, 5)),
+            ReadBounds::new((0, 0), (2, 2)),
+        );
+        #[rustfmt::skip]
+        assert_eq!(band_buff, vec![
+            0, 0, 1, 1, 1,
+            0, 0, 1, 1, 1,
+            2, 2, 3, 3, 3,
+            2, 2, 3, 3, 3,
+            2, 2, 3, 3, 3,
+        ]);
+    }
+
+    #[test]
+    fn double_resolution_bilinear() {
+        let reader = InMemoryBandReader::new(vec![0f32, 10., 20., 30.], (2, 2)).unwrap();
+        let read_bounds = ReadBounds::new((0, 0), (2, 2));
+        let view_bounds = ViewBounds::new((0, 0), (4, 4));
+        let read_buff = reader.read_to_buffer(&read_bounds).unwrap();
+        let mut band_buff = vec![0.; view_bounds.size()];
+        ResolutionChunker::new(&view_bounds, &read_bounds)
+            .read_resolution_interpolated(
+                read_buff.as_ref(),
+                &mut band_buff,
+                ResamplingMethod::Bilinear,
+            )
+            .unwrap();
+        // View pixel centers sit at -0.25, 0.25, 0.75 and 1.25 read pixels,
+        // the outer ones clamped to the edge.
+        #[rustfmt::skip]
+        assert_eq!(band_buff, vec![
+             0.0,  2.5,  7.5, 10.0,
+             5.0,  7.5, 12.5, 15.0,
+            15.0, 17.5, 22.5, 25.0,
+            20.0, 22.5, 27.5, 30.0,
+        ]);
+    }
+
+    #[test]
+    fn single_pixel_fills_view() {
+        let band_buff = read_chunked(
+            (2, 2),
+            ViewBounds::new((0, 0), (4, 3)),
+            ReadBounds::new((1, 1), (1, 1)),
+        );
+        assert_eq!(band_buff, vec![3; 12]);
+    }
+}
@@ -0,0 +1,69 @@
+use geo::{Coord, Intersects, Point, Polygon};
+
+use crate::{
+    buffer::Buffer,
+    components::{view::View, DataType},
+    errors::Result,
+};
+
+/// [View] over the bounding rect of a polygon,
+/// masking pixels outside of it.
+pub struct MaskedView<T: DataType> {
+    view: View<T>,
+    /// (Height, Width), `true` for pixels inside the polygon.
+    mask: Buffer<bool, 2>,
+}
+
+impl<T: DataType> MaskedView<T> {
+    /// Mask pixels of `view` whose center is not within `polygon`,
+    /// which must be in the view crs.
+    pub(crate) fn new(view: View<T>, polygon: &Polygon) -> Self {
+        let geo_bounds = view.geo_bounds();
+        let (width, height) = view.bounds_shape();
+        let mask = (0..height)
+            .flat_map(|row| (0..width).map(move |col| (col, row)))
+            .map(|(col, row)| {
+                let center = geo_bounds.pixel_coord(
+                    (width, height),
+                    Coord {
+                        x: col as f64 + 0.5,
+                        y: row as f64 + 0.5,
+                    },
+                );
+                polygon.intersects(&Point::from(center))
+            })
+            .collect();
+        let mask = Buffer::from_owned_parts(mask, [height, width]);
+        Self { view, mask }
+    }
+
+    /// Rectangular [View] read by [MaskedView::read].
+    pub fn view(&self) -> &View<T> {
+        &self.view
+    }
+
+    /// (Height, Width) mask, `true` for pixels inside the polygon.
+    pub fn mask(&self) -> &Buffer<bool, 2> {
+        &self.mask
+    }
+
+    /// Read, filling pixels outside the polygon
+    /// with the band nodata, or zero.
+    pub fn read(self) -> Result<Buffer<T, 3>> {
+        let nodata = self.view.nodata();
+        let mut buff = self.view.read()?;
+        let channel_len = self.mask.len().max(1);
+        buff.as_mut()
+            .chunks_exact_mut(channel_len)
+            .zip(nodata)
+            .for_each(|(band_data, nodata)| {
+                let fill = nodata.and_then(num::cast).unwrap_or_else(T::zero);
+                band_data
+                    .iter_mut()
+                    .zip(self.mask.as_ref())
+                    .filter(|(_, inside)| !**inside)
+                    .for_each(|(val, _)| *val = fill);
+            });
+        Ok(buff)
+    }
+}
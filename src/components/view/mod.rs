@@ -1,55 +1,255 @@
-mod band;
+pub mod band;
 mod chunking;
+pub mod masked;
+pub mod statistics;
 
-use geo::Coord;
-use log::info;
-use rayon::prelude::*;
+use geo::{Coord, Polygon, Rect};
+use geo_traits::RectTrait;
+use log::{info, warn};
+use rayon::{prelude::*, ThreadPoolBuilder};
 use std::{collections::HashSet, fmt::Debug, rc::Rc, sync::Arc};
 
 use crate::{
-    buffer::Buffer,
+    buffer::{Buffer, Stretch},
     components::{
-        bounds::{Bounds, GeoBounds, PixelBounds, ViewBounds},
+        band::BandReader,
+        bounds::{Bounds, GeoBounds, PixelBounds, ReadBounds, ViewBounds},
+        engines::mem_engine::InMemoryBandReader,
+        formula::Formula,
         raster::{band::RasterBand, group::RasterGroupInfo},
+        resampling::{resample_nearest, resample_window, ReadStrategy, ResamplingMethod},
         transforms::ViewReadTransform,
         view::{
-            band::{ReadBand, ViewBand},
+            band::{BandSnapshot, ReadBand, ViewBand},
             chunking::ResolutionChunker,
+            masked::MaskedView,
+            statistics::{BandStats, Histogram},
         },
-        DataType,
+        DataType, Metadata,
     },
     errors::{Result, RusterioError},
-    intersection::Intersection,
+    intersection::{Intersection, IntersectionError},
+    CoordUtils, Crs, CrsGeometry, Indexes,
 };
 
-pub trait Len {
-    fn len(&self) -> usize;
+/// View of raster bands within [ViewBounds].
+pub struct View<T: DataType> {
+    bounds: ViewBounds,
+    /// [GeoBounds] covered by `bounds`.
+    geo_bounds: GeoBounds,
+    bands: Rc<[ViewBand<T>]>,
 }
 
-impl<T> Len for Rc<[T]> {
-    fn len(&self) -> usize {
-        self.as_ref().len()
-    }
+/// Options for [SendSyncView::read_with_options].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReadOptions {
+    /// Used for bands with a resolution other than the view's.
+    pub strategy: ReadStrategy,
+    /// Read with at most this many threads,
+    /// or on the global rayon pool if `None`.
+    pub max_threads: Option<usize>,
+    /// Value of pixels outside of a band, e.g. of a [crate::Raster::stack_union],
+    /// instead of the band nodata, or zero.
+    pub fill_value: Option<f64>,
 }
 
-impl<T> Len for Arc<[T]> {
-    fn len(&self) -> usize {
-        self.as_ref().len()
-    }
-}
-
-pub struct View<Ba: Clone + Len> {
+/// [View] that can be shared across threads to read.
+#[derive(Clone)]
+pub struct SendSyncView<T: DataType> {
     bounds: ViewBounds,
-    bands: Ba,
+    bands: Arc<[ReadBand<T>]>,
 }
 
-pub type InfoView<T> = View<Rc<[ViewBand<T>]>>;
-pub type ReadView<T> = View<Arc<[ReadBand<T>]>>;
+impl<T: DataType> View<T> {
+    pub fn clip(&self, bounds: ViewBounds) -> Result<Self> {
+        let bounds = self.bounds.intersection(&bounds)?;
+        Ok(self.sub_view(bounds))
+    }
+
+    /// Clip to the bounding rect of `polygon`, which is reprojected
+    /// to the view crs if needed, masking pixels outside of it.
+    pub fn clip_polygon(&self, polygon: CrsGeometry<Polygon>) -> Result<MaskedView<T>> {
+        let polygon = polygon.with_crs(self.geo_bounds.crs())?;
+        let no_intersection = || RusterioError::NoIntersection(IntersectionError::NoIntersection);
+        let polygon_rect = polygon.bounding_rect().ok_or_else(no_intersection)?;
+        let (width, height) = self.bounds_shape();
+        // Pixels, relative to the view, covered by the polygon rect.
+        let to_pixel = |coord: Coord<f64>| {
+            let pixel = self.geo_bounds.pixel_position((width, height), coord);
+            Coord {
+                x: pixel.x.clamp(0., width as f64),
+                y: pixel.y.clamp(0., height as f64),
+            }
+        };
+        let top_left = to_pixel(Coord {
+            x: polygon_rect.min().x,
+            y: polygon_rect.max().y,
+        });
+        let bottom_right = to_pixel(Coord {
+            x: polygon_rect.max().x,
+            y: polygon_rect.min().y,
+        });
+        let min = Coord {
+            x: top_left.x.floor() as usize,
+            y: top_left.y.floor() as usize,
+        };
+        let max = Coord {
+            x: bottom_right.x.ceil() as usize,
+            y: bottom_right.y.ceil() as usize,
+        };
+        if min.x >= max.x || min.y >= max.y {
+            return Err(no_intersection());
+        }
+        let offset = self.bounds.offset() + min;
+        let view = self.sub_view(ViewBounds::from((offset, max - min)));
+        Ok(MaskedView::new(view, &polygon))
+    }
 
-impl<Ba: Clone + Len> View<Ba> {
+    /// Views of `tile_shape` (Width, Height) tiles covering the view
+    /// in row-major order, see [View::tile_bounds].
+    ///
+    /// Tiles are grown by `overlap` pixels on each side, within the view.
+    pub fn tiles(
+        &self,
+        tile_shape: (usize, usize),
+        overlap: usize,
+    ) -> Result<impl Iterator<Item = View<T>> + '_> {
+        let (offset, max) = (self.bounds.offset(), self.bounds.max());
+        Ok(self.tile_bounds(tile_shape)?.map(move |tile_bounds| {
+            let tile_min = tile_bounds.offset();
+            let tile_max = tile_bounds.max();
+            let min = Coord {
+                x: tile_min.x.saturating_sub(overlap).max(offset.x),
+                y: tile_min.y.saturating_sub(overlap).max(offset.y),
+            };
+            let max = Coord {
+                x: (tile_max.x + overlap).min(max.x),
+                y: (tile_max.y + overlap).min(max.y),
+            };
+            self.sub_view(ViewBounds::from((min, max - min)))
+        }))
+    }
+
+    /// View with `1/factor` of the pixels in each dimension,
+    /// e.g. for quick previews.
+    ///
+    /// Bands are downsampled on read, from overviews if any.
+    /// Right and bottom pixels that don't fill a decimated pixel are dropped.
+    ///
+    /// Fails with [RusterioError::InvalidFactor]
+    /// if `factor` is zero or larger than the view.
+    pub fn decimated(&self, factor: usize) -> Result<View<T>> {
+        let (width, height) = self.bounds_shape();
+        if factor == 0 || factor > width.min(height) {
+            return Err(RusterioError::InvalidFactor(factor));
+        }
+        if factor == 1 {
+            return Ok(self.sub_view(self.bounds.clone()));
+        }
+        let shape = (width / factor, height / factor);
+        let offset = self.bounds.offset();
+        let covered_bounds = ViewBounds::new(offset.x_y(), (shape.0 * factor, shape.1 * factor));
+        let bands = self
+            .bands
+            .iter()
+            .map(|view_band| ViewBand {
+                transform: view_band.transform.decimated(offset, factor),
+                ..view_band.clone()
+            })
+            .collect();
+        Ok(Self {
+            bounds: ViewBounds::new((0, 0), shape),
+            geo_bounds: self.sub_geo_bounds(&covered_bounds),
+            bands,
+        })
+    }
+
+    /// View of `bounds`, which must be within the view.
+    fn sub_view(&self, bounds: ViewBounds) -> Self {
+        let geo_bounds = self.sub_geo_bounds(&bounds);
+        let bands = Rc::clone(&self.bands);
+        Self {
+            bounds,
+            geo_bounds,
+            bands,
+        }
+    }
+
+    /// [GeoBounds] covered by the view.
+    pub fn geo_bounds(&self) -> &GeoBounds {
+        &self.geo_bounds
+    }
+
+    /// GDAL ordered `[xoff, a, b, yoff, d, e]` geo transform
+    /// of buffers read from the view.
+    pub fn geo_transform(&self) -> [f64; 6] {
+        self.geo_bounds.geo_transform(self.bounds_shape())
+    }
+
+    /// [GeoBounds] covered by `bounds` within the view.
+    fn sub_geo_bounds(&self, bounds: &ViewBounds) -> GeoBounds {
+        let relative_offset = bounds.offset() - self.bounds.offset();
+        let pixel_coord = |pixel: Coord<usize>| {
+            let pixel = Coord {
+                x: pixel.x as f64,
+                y: pixel.y as f64,
+            };
+            self.geo_bounds.pixel_coord(self.bounds_shape(), pixel)
+        };
+        GeoBounds::from(CrsGeometry::new(
+            Crs::new(self.geo_bounds.crs()),
+            Rect::new(
+                pixel_coord(relative_offset),
+                pixel_coord(relative_offset + bounds.shape()),
+            ),
+        ))
+    }
+
+    pub fn bounds_shape(&self) -> (usize, usize) {
+        self.bounds.shape().x_y()
+    }
+
+    /// Bounds of `tile_shape` (Width, Height) tiles
+    /// covering the view in row-major order.
+    ///
+    /// Tiles on the right and bottom edges are cropped to the view.
+    /// Errors if either tile dimension is zero.
+    pub fn tile_bounds(
+        &self,
+        tile_shape: (usize, usize),
+    ) -> Result<impl Iterator<Item = ViewBounds>> {
+        tile_bounds(&self.bounds, tile_shape)
+    }
+
+    /// Array shape (C, H, W)
+    pub fn array_shape(&self) -> [usize; 3] {
+        array_shape(&self.bounds, self.bands.len())
+    }
+
+    /// Names of the view bands, in channel order.
+    pub fn band_names(&self) -> Vec<String> {
+        self.bands
+            .iter()
+            .map(|view_band| view_band.info.name())
+            .collect()
+    }
+
+    /// Metadata of the band at channel `index`, without reading pixel data.
+    pub fn band_metadata(&self, index: usize) -> Result<Metadata> {
+        self.bands
+            .get(index)
+            .ok_or_else(|| {
+                RusterioError::BandNotFound(format!("index {index} of {} bands", self.bands.len()))
+            })?
+            .info
+            .metadata()
+    }
+}
+
+impl<T: DataType> SendSyncView<T> {
     pub fn clip(&self, bounds: ViewBounds) -> Result<Self> {
         let bounds = self.bounds.intersection(&bounds)?;
-        let bands = self.bands.clone();
+        let bands = Arc::clone(&self.bands);
         Ok(Self { bounds, bands })
     }
 
@@ -57,32 +257,99 @@ impl<Ba: Clone + Len> View<Ba> {
         self.bounds.shape().x_y()
     }
 
+    /// See [View::tile_bounds].
+    pub fn tile_bounds(
+        &self,
+        tile_shape: (usize, usize),
+    ) -> Result<impl Iterator<Item = ViewBounds>> {
+        tile_bounds(&self.bounds, tile_shape)
+    }
+
     /// Array shape (C, H, W)
     pub fn array_shape(&self) -> [usize; 3] {
-        let (width, height) = self.bounds_shape();
-        [self.bands.len(), height, width]
+        array_shape(&self.bounds, self.bands.len())
+    }
+
+    /// Names of the view bands, in channel order.
+    pub fn band_names(&self) -> Vec<String> {
+        self.bands
+            .iter()
+            .map(|read_band| read_band.snapshot.name.clone())
+            .collect()
+    }
+
+    /// [BandSnapshot] of the band at channel `index`.
+    pub fn band_snapshot(&self, index: usize) -> Result<&BandSnapshot> {
+        self.bands
+            .get(index)
+            .map(|read_band| read_band.snapshot.as_ref())
+            .ok_or_else(|| {
+                RusterioError::BandNotFound(format!("index {index} of {} bands", self.bands.len()))
+            })
     }
 }
 
-impl<T: DataType> Debug for InfoView<T> {
+fn tile_bounds(
+    bounds: &ViewBounds,
+    tile_shape: (usize, usize),
+) -> Result<impl Iterator<Item = ViewBounds>> {
+    let (tile_width, tile_height) = tile_shape;
+    if tile_width == 0 || tile_height == 0 {
+        return Err(RusterioError::InvalidTileShape(tile_shape));
+    }
+    let offset = bounds.offset();
+    let max = bounds.max();
+    Ok((offset.y..max.y).step_by(tile_height).flat_map(move |row| {
+        (offset.x..max.x).step_by(tile_width).map(move |col| {
+            ViewBounds::new(
+                (col, row),
+                (tile_width.min(max.x - col), tile_height.min(max.y - row)),
+            )
+        })
+    }))
+}
+
+fn array_shape(bounds: &ViewBounds, num_bands: usize) -> [usize; 3] {
+    let (width, height) = bounds.shape().x_y();
+    [num_bands, height, width]
+}
+
+impl<T: DataType> Debug for View<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let f = &mut f.debug_struct("View");
-        let bands: Vec<String> = self
+        f.field("bounds", &self.bounds)
+            .field("bands", &self.band_names())
+            .finish()
+    }
+}
+
+/// Number of band transforms shown when debug printing a [SendSyncView].
+const DEBUG_MAX_TRANSFORMS: usize = 3;
+
+impl<T: DataType> Debug for SendSyncView<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let f = &mut f.debug_struct("SendSyncView");
+        let transforms: Vec<&ViewReadTransform> = self
             .bands
             .iter()
-            .map(|view_band| view_band.info.name())
+            .take(DEBUG_MAX_TRANSFORMS)
+            .map(|read_band| &read_band.transform)
             .collect();
-        f.field("bounds", &self.bounds)
-            .field("bands", &bands)
+        f.field("bounds_shape", &self.bounds_shape())
+            .field("num_bands", &self.bands.len())
+            .field("transforms", &transforms)
             .finish()
     }
 }
 
-impl<T: DataType> InfoView<T> {
+impl<T: DataType> View<T> {
     pub fn new(
         bounds: GeoBounds,
         selected_bands: Box<[(&RasterGroupInfo, &RasterBand<T>)]>,
     ) -> Result<Self> {
+        if selected_bands.is_empty() {
+            return Err(RusterioError::NoBandsSelected);
+        }
         let view_group_infos: HashSet<&RasterGroupInfo> = selected_bands
             .iter()
             .map(|(group_idx, _)| *group_idx)
@@ -93,12 +360,17 @@ impl<T: DataType> InfoView<T> {
 
         let view_bounds = bounds.build_raster_view_bounds(view_transforms)?;
 
-        let bands = Rc::from_iter(selected_bands.iter().map(|(group_info, raster_band)| {
-            let transform = ViewReadTransform::new(&view_bounds, &bounds, &group_info.transform);
-            ViewBand::from((transform, *raster_band))
-        }));
+        let bands = selected_bands
+            .iter()
+            .map(|(group_info, raster_band)| {
+                let transform =
+                    ViewReadTransform::new(&view_bounds, &bounds, &group_info.transform)?;
+                ViewBand::new(transform, raster_band)
+            })
+            .collect::<Result<Rc<[ViewBand<T>]>>>()?;
         Ok(Self {
             bounds: view_bounds,
+            geo_bounds: bounds,
             bands,
         })
     }
@@ -110,44 +382,1016 @@ impl<T: DataType> InfoView<T> {
             .collect()
     }
 
-    pub fn to_send_sync(self) -> ReadView<T> {
+    /// Band info is kept as a [BandSnapshot],
+    /// e.g. for [SendSyncView::band_names].
+    pub fn to_send_sync(self) -> SendSyncView<T> {
         let bands = Arc::from_iter(self.par_bands());
         let bounds = self.bounds;
-        View { bounds, bands }
+        SendSyncView { bounds, bands }
     }
 
     pub fn read(self) -> Result<Buffer<T, 3>> {
         self.to_send_sync().read()
     }
+
+    /// Nodata value of each band, if any.
+    pub fn nodata(&self) -> Vec<Option<f64>> {
+        self.bands
+            .iter()
+            .map(|view_band| view_band.nodata())
+            .collect()
+    }
+
+    /// See [SendSyncView::read_masked].
+    pub fn read_masked(self) -> Result<(Buffer<T, 3>, Buffer<bool, 3>)> {
+        self.to_send_sync().read_masked()
+    }
+
+    /// See [SendSyncView::read_with_strategy].
+    pub fn read_with_strategy(self, strategy: ReadStrategy) -> Result<Buffer<T, 3>> {
+        self.to_send_sync().read_with_strategy(strategy)
+    }
+
+    /// See [SendSyncView::read_with_options].
+    pub fn read_with_options(self, options: ReadOptions) -> Result<Buffer<T, 3>> {
+        self.to_send_sync().read_with_options(options)
+    }
+
+    /// See [SendSyncView::to_rgb8].
+    pub fn to_rgb8(self, band_order: [usize; 3], stretch: Stretch) -> Result<Buffer<u8, 3>> {
+        self.to_send_sync().to_rgb8(band_order, stretch)
+    }
+
+    /// See [SendSyncView::save_preview].
+    #[cfg(feature = "image")]
+    pub fn save_preview(
+        self,
+        path: impl AsRef<std::path::Path>,
+        band_order: [usize; 3],
+        stretch: Stretch,
+    ) -> Result<()> {
+        self.to_send_sync().save_preview(path, band_order, stretch)
+    }
+
+    /// See [SendSyncView::statistics].
+    pub fn statistics(self, ignore_nodata: bool) -> Result<Vec<BandStats>> {
+        self.to_send_sync().statistics(ignore_nodata)
+    }
+
+    /// See [SendSyncView::histogram].
+    pub fn histogram(self, bins: usize, range: Option<(f64, f64)>) -> Result<Vec<Histogram>> {
+        self.to_send_sync().histogram(bins, range)
+    }
+
+    /// Normalized difference vegetation index, `(nir - red) / (nir + red)`,
+    /// of bands named `nir` and `red` into a single channel [Buffer].
+    ///
+    /// Pixels where either band is nodata, or both are zero, are NaN.
+    pub fn ndvi(self, nir: &str, red: &str) -> Result<Buffer<f32, 3>> {
+        let ndvi = self
+            .to_send_sync()
+            .eval_formula(&Formula::normalized_difference(nir, red))?;
+        let (data, shape) = ndvi.to_owned_parts();
+        Ok(Buffer::from_owned_parts(
+            data.iter().map(|val| *val as f32).collect(),
+            shape,
+        ))
+    }
 }
 
-impl<T: DataType> ReadView<T> {
+impl<T: DataType> SendSyncView<T> {
     pub fn read(&self) -> Result<Buffer<T, 3>> {
-        let mut buff = Buffer::new(self.array_shape());
-        let view_bounds = &self.bounds;
-        buff.as_mut()
-            .par_chunks_mut(view_bounds.size())
-            .zip(self.bands.into_par_iter())
-            .map(|(band_buff, read_band)| {
-                // TODO: chunk!?
-                let read_bounds = &view_bounds.as_read_bounds(&read_band.transform);
-                info!("reading {:?} as {:?}", view_bounds, read_bounds);
-                match read_bounds.shape() {
-                    Coord { x: 1, y: 1 } => Ok::<_, RusterioError>(
-                        band_buff.fill(read_band.reader.read_pixel(read_bounds.offset())?),
+        self.read_with_strategy(ReadStrategy::default())
+    }
+
+    /// Nodata value of each band, if any.
+    pub fn nodata(&self) -> Vec<Option<f64>> {
+        self.bands
+            .iter()
+            .map(|read_band| read_band.nodata())
+            .collect()
+    }
+
+    /// Read along with a (C, H, W) mask, `true` where pixels are nodata.
+    pub fn read_masked(&self) -> Result<(Buffer<T, 3>, Buffer<bool, 3>)> {
+        let buff = self.read()?;
+        let mask = nodata_mask(&buff, &self.nodata());
+        Ok((buff, mask))
+    }
+
+    /// Read bands at `band_order` as a display ready RGB [Buffer] (C, H, W),
+    /// each channel scaled into `u8` with `stretch`, ignoring nodata.
+    ///
+    /// Use [Buffer::transpose_to_hwc] for interleaved (H, W, C) pixels.
+    pub fn to_rgb8(&self, band_order: [usize; 3], stretch: Stretch) -> Result<Buffer<u8, 3>> {
+        let rgb_view = self.select_bands(&band_order)?;
+        Ok(rgb_view.read()?.stretch(stretch, &rgb_view.nodata()))
+    }
+
+    /// Write bands at `band_order` as an RGB image at `path`, see [SendSyncView::to_rgb8].
+    ///
+    /// Encoded as PNG or JPEG by the `path` extension.
+    #[cfg(feature = "image")]
+    pub fn save_preview(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        band_order: [usize; 3],
+        stretch: Stretch,
+    ) -> Result<()> {
+        let (data, [height, width, _]) = self
+            .to_rgb8(band_order, stretch)?
+            .to_channel_last()
+            .to_owned_parts();
+        let image = image::RgbImage::from_raw(width as u32, height as u32, data.into_vec())
+            .expect("rgb buffer holds width x height pixels");
+        Ok(image.save(path)?)
+    }
+
+    /// View of bands at `band_indexes`, in that order.
+    fn select_bands(&self, band_indexes: &[usize]) -> Result<SendSyncView<T>> {
+        let bands = band_indexes
+            .iter()
+            .map(|idx| {
+                self.bands.get(*idx).cloned().ok_or_else(|| {
+                    RusterioError::BandNotFound(format!(
+                        "index {idx} of {} bands",
+                        self.bands.len()
+                    ))
+                })
+            })
+            .collect::<Result<Arc<[ReadBand<T>]>>>()?;
+        Ok(SendSyncView {
+            bounds: self.bounds.clone(),
+            bands,
+        })
+    }
+
+    /// Read and evaluate `formula` over the bands it names at each pixel,
+    /// in parallel, into a single channel [Buffer].
+    ///
+    /// Pixels where any of those bands is nodata are NaN.
+    pub(crate) fn eval_formula(&self, formula: &Formula) -> Result<Buffer<f64, 3>> {
+        let band_indexes = Indexes::by_name(formula.band_names().iter().cloned())
+            .resolve_names(|| Ok(self.band_names()))?
+            .indexes_from(self.bands.len());
+        let view = self.select_bands(&band_indexes)?;
+        let nodata = view.nodata();
+        let buff = view.read()?;
+        let [num_bands, height, width] = view.array_shape();
+        let channel_len = height * width;
+        let data = (0..channel_len)
+            .into_par_iter()
+            .map_init(
+                || vec![0.; num_bands],
+                |band_values, pixel_idx| {
+                    let pixel_vals = buff.as_ref()[pixel_idx..].iter().step_by(channel_len);
+                    for ((band_value, val), nodata) in
+                        band_values.iter_mut().zip(pixel_vals).zip(&nodata)
+                    {
+                        let val = val.to_f64().unwrap_or(f64::NAN);
+                        if Some(val) == *nodata {
+                            return f64::NAN;
+                        }
+                        *band_value = val;
+                    }
+                    formula.eval(band_values)
+                },
+            )
+            .collect();
+        Ok(Buffer::from_owned_parts(data, [1, height, width]))
+    }
+
+    /// Read and compute [BandStats] of each band in parallel,
+    /// skipping pixels equal to the band nodata if `ignore_nodata`.
+    pub fn statistics(&self, ignore_nodata: bool) -> Result<Vec<BandStats>> {
+        let buff = self.read()?;
+        let channel_len = self.bounds.size().max(1);
+        Ok(buff
+            .as_ref()
+            .par_chunks(channel_len)
+            .zip(self.bands.par_iter())
+            .map(|(band_data, read_band)| {
+                BandStats::from_band(band_data, read_band.nodata().filter(|_| ignore_nodata))
+            })
+            .collect())
+    }
+
+    /// Read and compute a [Histogram] of each band in parallel,
+    /// with `bins` bins over `range`, or the band min and max.
+    /// Pixels equal to the band nodata are not counted.
+    ///
+    /// Fails with [RusterioError::InvalidBinCount] if `bins` is zero.
+    pub fn histogram(&self, bins: usize, range: Option<(f64, f64)>) -> Result<Vec<Histogram>> {
+        if bins == 0 {
+            return Err(RusterioError::InvalidBinCount(bins));
+        }
+        let buff = self.read()?;
+        let channel_len = self.bounds.size().max(1);
+        Ok(buff
+            .as_ref()
+            .par_chunks(channel_len)
+            .zip(self.bands.par_iter())
+            .map(|(band_data, read_band)| {
+                Histogram::from_band(band_data, bins, range, read_band.nodata())
+            })
+            .collect())
+    }
+
+    /// Read, using `strategy` for bands
+    /// with a resolution other than the view's.
+    pub fn read_with_strategy(&self, strategy: ReadStrategy) -> Result<Buffer<T, 3>> {
+        self.read_with_options(ReadOptions {
+            strategy,
+            ..Default::default()
+        })
+    }
+
+    /// Read with `options`, limiting parallelism
+    /// to a dedicated pool if `max_threads` is set.
+    pub fn read_with_options(&self, options: ReadOptions) -> Result<Buffer<T, 3>> {
+        let read = || {
+            let mut buff = Buffer::new(self.array_shape());
+            let view_bounds = &self.bounds;
+            buff.as_mut()
+                .par_chunks_mut(view_bounds.size())
+                .zip(self.bands.into_par_iter())
+                .map(|(band_buff, read_band)| {
+                    read_band_into(view_bounds, read_band, &options, band_buff)
+                })
+                .collect::<Result<Vec<()>>>()?;
+            Ok(buff)
+        };
+        match options.max_threads {
+            Some(num_threads) => ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()?
+                .install(read),
+            None => read(),
+        }
+    }
+
+    /// Like [SendSyncView::read], reading each band
+    /// on a blocking tokio task. Must be awaited within a tokio runtime.
+    #[cfg(feature = "tokio")]
+    pub async fn read_async(&self) -> Result<Buffer<T, 3>> {
+        let band_len = self.bounds.size();
+        let band_reads: Vec<_> = (0..self.bands.len())
+            .map(|band_idx| {
+                let view = self.clone();
+                tokio::task::spawn_blocking(move || {
+                    let mut band_buff = vec![T::zero(); band_len];
+                    read_band_into(
+                        &view.bounds,
+                        &view.bands[band_idx],
+                        &ReadOptions::default(),
+                        &mut band_buff,
+                    )
+                    .map(|_| band_buff)
+                })
+            })
+            .collect();
+        let mut data = Vec::with_capacity(band_len * self.bands.len());
+        for band_read in band_reads {
+            data.extend(band_read.await??);
+        }
+        Ok(Buffer::from_owned_parts(data, self.array_shape()))
+    }
+}
+
+/// Read `read_band` within `view_bounds` into `band_buff`,
+/// using `options.strategy` if its resolution is not the view's.
+///
+/// Pixels read from outside of the band, e.g. by a view flush against
+/// its bottom right edge or of a [crate::Raster::stack_union],
+/// are filled with `options.fill_value`, or the band nodata, or zero.
+fn read_band_into<T: DataType>(
+    view_bounds: &ViewBounds,
+    read_band: &ReadBand<T>,
+    options: &ReadOptions,
+    band_buff: &mut [T],
+) -> Result<()> {
+    // TODO: chunk!?
+    let (read_offset, read_shape) = view_bounds.as_signed_read_window(&read_band.transform)?;
+    let band_size = read_band.snapshot.size;
+    let within_band = read_offset.try_cast::<usize>().ok().filter(|offset| {
+        offset.x + read_shape.x <= band_size.0 && offset.y + read_shape.y <= band_size.1
+    });
+    if let Some(read_offset) = within_band {
+        let read_bounds = ReadBounds::from((read_offset, read_shape));
+        info!("reading {} as {}", view_bounds, read_bounds);
+        read_window_into(
+            view_bounds,
+            &read_bounds,
+            read_band.reader.as_ref(),
+            &read_band.overviews,
+            options.strategy,
+            band_buff,
+        )?;
+    } else {
+        info!(
+            "reading {} as {:?} pixels at {:?}, partly outside band of size {:?}",
+            view_bounds, read_shape, read_offset, band_size
+        );
+        let fill_value = options
+            .fill_value
+            .or(read_band.nodata())
+            .and_then(num::cast::<f64, T>)
+            .unwrap_or_else(T::zero);
+        let edge_reader = read_within_band(
+            read_band.reader.as_ref(),
+            (read_offset, read_shape),
+            band_size,
+            fill_value,
+        )?;
+        read_window_into(
+            view_bounds,
+            &ReadBounds::from((Coord::zero(), read_shape)),
+            &edge_reader,
+            &[],
+            options.strategy,
+            band_buff,
+        )?;
+    }
+    if let Some(nodata) = read_band.nodata() {
+        warn_if_all_nodata(band_buff, nodata, view_bounds);
+    }
+    Ok(())
+}
+
+/// In memory band of the (offset, shape) `read_window` shape, holding pixels
+/// of `reader` within its band `size` (Width, Height) and `fill_value` elsewhere.
+fn read_within_band<T: DataType>(
+    reader: &dyn BandReader<T>,
+    (read_offset, read_shape): (Coord<isize>, Coord<usize>),
+    size: (usize, usize),
+    fill_value: T,
+) -> Result<InMemoryBandReader<T>> {
+    let mut data = vec![fill_value; read_shape.x * read_shape.y];
+    let clamp = |val: isize, max: usize| val.clamp(0, max as isize) as usize;
+    let within_min = Coord {
+        x: clamp(read_offset.x, size.0),
+        y: clamp(read_offset.y, size.1),
+    };
+    let within_max = Coord {
+        x: clamp(read_offset.x + read_shape.x as isize, size.0),
+        y: clamp(read_offset.y + read_shape.y as isize, size.1),
+    };
+    if within_min.x < within_max.x && within_min.y < within_max.y {
+        let within_bounds = ReadBounds::from((within_min, within_max - within_min));
+        let within_buff = reader.read_to_buffer(&within_bounds)?;
+        let relative_offset = Coord {
+            x: (within_min.x as isize - read_offset.x) as usize,
+            y: (within_min.y as isize - read_offset.y) as usize,
+        };
+        let within_width = within_bounds.width();
+        within_buff
+            .as_ref()
+            .chunks_exact(within_width.max(1))
+            .zip(relative_offset.y..)
+            .for_each(|(row, row_idx)| {
+                let row_start = row_idx * read_shape.x + relative_offset.x;
+                data[row_start..row_start + within_width].copy_from_slice(row)
+            });
+    }
+    InMemoryBandReader::new(data, read_shape.x_y())
+}
+
+/// Read `read_bounds` of `reader`, or of one of its `overviews`,
+/// into `band_buff` of `view_bounds` shape.
+///
+/// Engines resampling natively with [ReadStrategy::GdalResample]
+/// pick overviews themselves.
+fn read_window_into<T: DataType>(
+    view_bounds: &ViewBounds,
+    read_bounds: &ReadBounds,
+    reader: &dyn BandReader<T>,
+    overviews: &[(usize, usize)],
+    strategy: ReadStrategy,
+    band_buff: &mut [T],
+) -> Result<()> {
+    let view_shape = view_bounds.shape();
+    let read_shape = read_bounds.shape();
+    let natively_resampled =
+        matches!(strategy, ReadStrategy::GdalResample(_)) && reader.resamples_natively();
+    let overview = if !overviews.is_empty()
+        && !natively_resampled
+        && read_shape.x > view_shape.x
+        && read_shape.y > view_shape.y
+    {
+        let raster_size = reader.raster_size()?;
+        select_overview(raster_size, overviews, read_bounds, view_shape.x_y())
+            .map(|(level, overview_bounds)| (level, overview_bounds, raster_size))
+    } else {
+        None
+    };
+    let read =
+        match (read_shape, overview) {
+            (_, Some((level, overview_bounds, raster_size))) => {
+                info!("reading overview {} as {}", level, overview_bounds);
+                let mut overview_buff = vec![T::zero(); overview_bounds.size()];
+                reader.read_overview_into_slice(level, &overview_bounds, &mut overview_buff)?;
+                // `read_bounds` within the overview window, which is rounded outwards.
+                let overview_size = overviews[level];
+                let scale = Coord {
+                    x: overview_size.0 as f64 / raster_size.0 as f64,
+                    y: overview_size.1 as f64 / raster_size.1 as f64,
+                };
+                let (read_min, overview_min) = (read_bounds.min(), overview_bounds.min());
+                let window_offset = Coord {
+                    x: read_min.x as f64 * scale.x - overview_min.x as f64,
+                    y: read_min.y as f64 * scale.y - overview_min.y as f64,
+                };
+                let window_shape = Coord {
+                    x: read_shape.x as f64 * scale.x,
+                    y: read_shape.y as f64 * scale.y,
+                };
+                let method = match strategy {
+                    ReadStrategy::Interpolated(method) => method,
+                    ReadStrategy::GdalResample(algorithm) => algorithm.fallback_method(),
+                    ReadStrategy::Chunked => ResamplingMethod::NearestNeighbour,
+                };
+                resample_window(
+                    &overview_buff,
+                    overview_bounds.shape().x_y(),
+                    (window_offset, window_shape),
+                    view_shape.x_y(),
+                    band_buff,
+                    method,
+                );
+                Ok(())
+            }
+            (Coord { x: 1, y: 1 }, _) => {
+                Ok::<_, RusterioError>(band_buff.fill(reader.read_pixel(read_bounds.offset())?))
+            }
+            (read_shape, _) if read_shape == view_shape => {
+                Ok(reader.read_into_slice(read_bounds, band_buff)?)
+            }
+            (read_shape, _) if read_shape.x > view_shape.x || read_shape.y > view_shape.y => {
+                // Band is finer than the view, e.g. in a decimated view.
+                info!("downsampling band of shape: {:?}", read_shape);
+                match strategy {
+                    ReadStrategy::GdalResample(algorithm) => reader.read_into_slice_resampled(
+                        read_bounds,
+                        view_shape.x_y(),
+                        band_buff,
+                        algorithm,
                     ),
-                    read_shape if read_shape == view_bounds.shape() => {
-                        Ok(read_band.reader.read_into_slice(read_bounds, band_buff)?)
+                    ReadStrategy::Interpolated(method) => {
+                        let read_buff = reader.read_to_buffer(read_bounds)?;
+                        resample_window(
+                            read_buff.as_ref(),
+                            read_shape.x_y(),
+                            (Coord::zero(), read_shape.try_cast()?),
+                            view_shape.x_y(),
+                            band_buff,
+                            method,
+                        );
+                        Ok(())
                     }
-                    read_shape => {
-                        info!("band has different shape: {:?}", read_shape);
-                        let read_buff = read_band.reader.read_to_buffer(read_bounds)?;
+                    ReadStrategy::Chunked => {
+                        let read_buff = reader.read_to_buffer(read_bounds)?;
+                        resample_nearest(
+                            read_buff.as_ref(),
+                            read_shape.x_y(),
+                            view_shape.x_y(),
+                            band_buff,
+                        );
+                        Ok(())
+                    }
+                }
+            }
+            (read_shape, _) => {
+                info!("band has different shape: {:?}", read_shape);
+                match strategy {
+                    ReadStrategy::Interpolated(method) => {
+                        let read_buff = reader.read_to_buffer(read_bounds)?;
+                        ResolutionChunker::new(view_bounds, read_bounds)
+                            .read_resolution_interpolated(read_buff.as_ref(), band_buff, method)
+                    }
+                    ReadStrategy::GdalResample(algorithm) if reader.resamples_natively() => reader
+                        .read_into_slice_resampled(
+                            read_bounds,
+                            view_bounds.shape().x_y(),
+                            band_buff,
+                            algorithm,
+                        ),
+                    ReadStrategy::Chunked | ReadStrategy::GdalResample(_) => {
+                        let read_buff = reader.read_to_buffer(read_bounds)?;
                         ResolutionChunker::new(view_bounds, read_bounds)
                             .read_resolution_chucked(read_buff.as_ref(), band_buff)
                     }
                 }
+            }
+        };
+    read
+}
+
+/// Coarsest overview, of `overviews` sizes, with at least the resolution
+/// needed to read `read_bounds` of a band of `raster_size` into `view_shape`,
+/// and `read_bounds` in its pixels.
+fn select_overview(
+    raster_size: (usize, usize),
+    overviews: &[(usize, usize)],
+    read_bounds: &ReadBounds,
+    view_shape: (usize, usize),
+) -> Option<(usize, ReadBounds)> {
+    let read_shape = read_bounds.shape();
+    let downsample_factor = f64::min(
+        read_shape.x as f64 / view_shape.0 as f64,
+        read_shape.y as f64 / view_shape.1 as f64,
+    );
+    let (level, overview_size) = overviews
+        .iter()
+        .enumerate()
+        .filter(|(_, (width, _))| raster_size.0 as f64 / *width as f64 <= downsample_factor)
+        .min_by_key(|(_, (width, _))| *width)?;
+    let scale = |val: usize, native: usize, overview: usize| val * overview / native;
+    let (min, max) = (read_bounds.min(), read_bounds.max());
+    let overview_min = Coord {
+        x: scale(min.x, raster_size.0, overview_size.0),
+        y: scale(min.y, raster_size.1, overview_size.1),
+    };
+    let overview_max = Coord {
+        x: (max.x * overview_size.0)
+            .div_ceil(raster_size.0)
+            .clamp(overview_min.x + 1, overview_size.0),
+        y: (max.y * overview_size.1)
+            .div_ceil(raster_size.1)
+            .clamp(overview_min.y + 1, overview_size.1),
+    };
+    Some((
+        level,
+        ReadBounds::from((overview_min, overview_max - overview_min)),
+    ))
+}
+
+/// (C, H, W) mask, `true` where pixels of `buff` equal the `nodata` of their channel.
+fn nodata_mask<T: DataType>(buff: &Buffer<T, 3>, nodata: &[Option<f64>]) -> Buffer<bool, 3> {
+    let channel_len = buff.height() * buff.width();
+    let mask = buff
+        .as_ref()
+        .chunks(channel_len.max(1))
+        .zip(nodata)
+        .flat_map(|(channel, nodata)| {
+            let nodata_val = nodata.and_then(num::cast::<f64, T>);
+            channel.iter().map(move |val| Some(*val) == nodata_val)
+        })
+        .collect();
+    Buffer::from_owned_parts(mask, buff.shape())
+}
+
+/// Warn if every pixel in `band_buff` is `nodata`,
+/// which often points to a wrong band index, file path or nodata handling.
+fn warn_if_all_nodata<T: DataType>(band_buff: &[T], nodata: f64, view_bounds: &ViewBounds) {
+    let Some(nodata_val) = num::cast::<f64, T>(nodata) else {
+        return;
+    };
+    if band_buff.iter().all(|val| *val == nodata_val) {
+        warn!("band is entirely nodata ({nodata}) in {view_bounds}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        components::{
+            band::{BandInfo, BandReader},
+            engines::mem_engine::{InMemoryBandInfo, InMemoryBandReader},
+            resampling::GdalResamplingAlgorithm,
+            transforms::ReadGeoTransform,
+        },
+        Indexes, Raster,
+    };
+    use geo::{LineString, Rect};
+
+    /// Single band 5x3 raster holding `0..15`.
+    fn view_5x3_raster() -> Raster<u16> {
+        let info: Rc<dyn BandInfo> = Rc::new(InMemoryBandInfo {
+            name: String::from("band"),
+        });
+        let bounds = GeoBounds::from(CrsGeometry::new(
+            Crs::new("EPSG:32633"),
+            Rect::new((0., 0.), (50., 30.)),
+        ));
+        Raster::from_in_memory_bands(
+            std::iter::once((info, (0..15).collect())),
+            (5, 3),
+            bounds,
+            String::from("5x3"),
+        )
+        .unwrap()
+    }
+
+    /// View of [view_5x3_raster].
+    fn view_5x3() -> View<u16> {
+        view_5x3_raster().view(None, Indexes::all()).unwrap()
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn save_preview() {
+        let path = std::env::temp_dir().join("rusterio_save_preview.png");
+        let view = view_5x3();
+        let (width, height) = view.bounds_shape();
+        view.save_preview(&path, [0, 0, 0], Stretch::MinMax)
+            .unwrap();
+        let image = image::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            (image.width() as usize, image.height() as usize),
+            (width, height)
+        );
+    }
+
+    #[test]
+    fn decimated() {
+        let info: Rc<dyn BandInfo> = Rc::new(InMemoryBandInfo {
+            name: String::from("band"),
+        });
+        let bounds = GeoBounds::from(CrsGeometry::new(
+            Crs::new("EPSG:32633"),
+            Rect::new((0., 0.), (10240., 10240.)),
+        ));
+        let raster = Raster::<u32>::from_in_memory_bands(
+            std::iter::once((info, (0..1024 * 1024).collect())),
+            (1024, 1024),
+            bounds,
+            String::from("1024x1024"),
+        )
+        .unwrap();
+        let view = raster.view(None, Indexes::all()).unwrap();
+        let decimated = view.decimated(8).unwrap();
+        assert_eq!(decimated.geo_bounds().min(), view.geo_bounds().min());
+        assert_eq!(decimated.geo_bounds().max(), view.geo_bounds().max());
+        let buff = decimated.read().unwrap();
+        assert_eq!(buff.shape(), [1, 128, 128]);
+        // Top left pixel of each 8x8 block.
+        assert_eq!(buff.as_ref()[..2], [0, 8]);
+        assert_eq!(buff.as_ref()[128], 8 * 1024);
+        let clipped = view.clip(ViewBounds::new((16, 16), (64, 64))).unwrap();
+        let buff = clipped.decimated(8).unwrap().read().unwrap();
+        assert_eq!(buff.shape(), [1, 8, 8]);
+        assert_eq!(buff.as_ref()[0], 16 * 1024 + 16);
+
+        let view = view_5x3();
+        let same = view.decimated(1).unwrap();
+        assert_eq!(same.bounds_shape(), view.bounds_shape());
+        assert_eq!(same.read().unwrap().as_ref(), view.read().unwrap().as_ref());
+        assert!(matches!(
+            view_5x3().decimated(0),
+            Err(RusterioError::InvalidFactor(0))
+        ));
+    }
+
+    #[test]
+    fn read_past_band_edge() {
+        // 5x3 band holding `0..15`, viewed over an extra column and row.
+        let group_info = RasterGroupInfo {
+            description: String::from("5x3"),
+            transform: ReadGeoTransform::new(10., 0., 0., 0., -10., 30., Crs::new("EPSG:32633"))
+                .try_inverse()
+                .unwrap(),
+            metadata: Metadata::default(),
+        };
+        let band = RasterBand {
+            info: Rc::new(InMemoryBandInfo {
+                name: String::from("band"),
+            }),
+            reader: Arc::new(InMemoryBandReader::new((0..15).collect(), (5, 3)).unwrap()),
+        };
+        let bounds = GeoBounds::from(CrsGeometry::new(
+            Crs::new("EPSG:32633"),
+            Rect::new((0., -10.), (60., 30.)),
+        ));
+        let view = View::<u16>::new(bounds, Box::new([(&group_info, &band)])).unwrap();
+        assert_eq!(view.bounds_shape(), (6, 4));
+        let corner = view.clip(ViewBounds::new((4, 2), (2, 2))).unwrap();
+        assert_eq!(corner.read().unwrap().as_ref(), [14, 0, 0, 0]);
+    }
+
+    #[test]
+    fn no_bands_selected() {
+        let raster = view_5x3_raster();
+        let view = raster.view(None, Indexes::from(([], false)));
+        assert!(matches!(view, Err(RusterioError::NoBandsSelected)));
+    }
+
+    #[test]
+    fn clip_triangle() {
+        let view = view_5x3();
+        let triangle = CrsGeometry::new(
+            Crs::new("EPSG:32633"),
+            Polygon::new(
+                LineString::from(vec![(0., 30.), (40., 30.), (0., 0.), (0., 30.)]),
+                vec![],
+            ),
+        );
+        let masked_view = view.clip_polygon(triangle).unwrap();
+        assert_eq!(masked_view.view().bounds_shape(), (4, 3));
+        #[rustfmt::skip]
+        assert_eq!(masked_view.mask().as_ref(), &[
+            true, true,  true,  false,
+            true, true,  false, false,
+            true, false, false, false,
+        ]);
+        #[rustfmt::skip]
+        assert_eq!(masked_view.read().unwrap().as_ref(), &[
+            0,  1, 2, 0,
+            5,  6, 0, 0,
+            10, 0, 0, 0,
+        ]);
+    }
+
+    #[test]
+    fn geo_transform() {
+        let view = view_5x3();
+        assert_eq!(view.geo_transform(), [0., 10., 0., 30., 0., -10.]);
+        let clipped = view.clip(ViewBounds::new((1, 1), (2, 2))).unwrap();
+        assert_eq!(clipped.geo_transform(), [10., 10., 0., 20., 0., -10.]);
+    }
+
+    #[test]
+    fn ndvi() {
+        let bands =
+            [("B04", vec![10u16, 0, 30, 0]), ("B08", vec![30, 20, 10, 0])].map(|(name, data)| {
+                let name = String::from(name);
+                let info: Rc<dyn BandInfo> = Rc::new(InMemoryBandInfo { name });
+                (info, data)
+            });
+        let bounds = GeoBounds::from(CrsGeometry::new(
+            Crs::new("EPSG:32633"),
+            Rect::new((0., 0.), (20., 20.)),
+        ));
+        let raster =
+            Raster::from_in_memory_bands(bands.into_iter(), (2, 2), bounds, String::from("2x2"))
+                .unwrap();
+        let view = raster.view(None, Indexes::all()).unwrap();
+        let ndvi = view.ndvi("B08", "B04").unwrap();
+        assert_eq!(ndvi.shape(), [1, 2, 2]);
+        assert_eq!(&ndvi.as_ref()[..3], &[0.5, 1., -0.5]);
+        assert!(ndvi.as_ref()[3].is_nan());
+    }
+
+    #[test]
+    fn send_sync_keeps_band_info() {
+        let view = view_5x3();
+        let band_names = view.band_names();
+        let send_sync_view = view.to_send_sync();
+        send_sync_view.read().unwrap();
+        assert_eq!(send_sync_view.band_names(), band_names);
+        let snapshot = send_sync_view.band_snapshot(0).unwrap();
+        assert_eq!(snapshot.description, "band");
+        assert_eq!(snapshot.nodata, None);
+    }
+
+    #[test]
+    fn tiles_reconstruct_view() {
+        let view = view_5x3();
+        let mut data = vec![0; 15];
+        let tiles: Vec<View<u16>> = view.tiles((2, 2), 0).unwrap().collect();
+        assert_eq!(tiles.len(), 6);
+        for tile in tiles {
+            let offset = tile.bounds.offset();
+            let (width, _) = tile.bounds_shape();
+            let buff = tile.read().unwrap();
+            buff.as_ref()
+                .chunks(width)
+                .enumerate()
+                .for_each(|(row, row_data)| {
+                    let start = (offset.y + row) * 5 + offset.x;
+                    data[start..start + width].copy_from_slice(row_data)
+                });
+        }
+        assert_eq!(data, (0..15).collect::<Vec<u16>>());
+    }
+
+    #[test]
+    fn tiles_zero_size() {
+        let view = view_5x3();
+        assert!(matches!(
+            view.tile_bounds((0, 2)),
+            Err(RusterioError::InvalidTileShape((0, 2)))
+        ));
+        assert!(view.tiles((2, 0), 1).is_err());
+        assert!(view.to_send_sync().tile_bounds((0, 0)).is_err());
+    }
+
+    #[test]
+    fn tiles_overlap() {
+        let view = view_5x3();
+        let shapes: Vec<(usize, usize)> = view
+            .tiles((2, 2), 1)
+            .unwrap()
+            .map(|tile| tile.bounds_shape())
+            .collect();
+        assert_eq!(shapes, [(3, 3), (4, 3), (2, 3), (3, 2), (4, 2), (2, 2)]);
+        let last = view.tiles((2, 2), 1).unwrap().last().unwrap();
+        assert_eq!(last.geo_bounds().min(), Coord { x: 30., y: 0. });
+        assert_eq!(last.read().unwrap().as_ref(), &[8, 9, 13, 14]);
+    }
+
+    /// 8x8 band holding `0..64` with a 4x4 overview
+    /// holding `100..116`, recording overview reads.
+    #[derive(Debug)]
+    struct OverviewBandReader {
+        native: InMemoryBandReader<u16>,
+        overview: InMemoryBandReader<u16>,
+        overview_reads: std::sync::Mutex<Vec<(Coord<usize>, Coord<usize>)>>,
+    }
+
+    impl OverviewBandReader {
+        fn new() -> Self {
+            Self {
+                native: InMemoryBandReader::new((0..64).collect(), (8, 8)).unwrap(),
+                overview: InMemoryBandReader::new((100..116).collect(), (4, 4)).unwrap(),
+                overview_reads: Default::default(),
+            }
+        }
+    }
+
+    impl BandReader<u16> for OverviewBandReader {
+        fn raster_size(&self) -> Result<(usize, usize)> {
+            self.native.raster_size()
+        }
+        fn read_into_slice(&self, bounds: &ReadBounds, slice: &mut [u16]) -> Result<()> {
+            self.native.read_into_slice(bounds, slice)
+        }
+        fn read_to_buffer(&self, bounds: &ReadBounds) -> Result<Buffer<u16, 1>> {
+            self.native.read_to_buffer(bounds)
+        }
+        fn read_pixel(&self, offset: Coord<usize>) -> Result<u16> {
+            self.native.read_pixel(offset)
+        }
+        fn read_overview_into_slice(
+            &self,
+            _level: usize,
+            bounds: &ReadBounds,
+            slice: &mut [u16],
+        ) -> Result<()> {
+            self.overview_reads
+                .lock()
+                .unwrap()
+                .push((bounds.offset(), bounds.shape()));
+            self.overview.read_into_slice(bounds, slice)
+        }
+        fn hash_source(&self, state: &mut dyn std::hash::Hasher) {
+            self.native.hash_source(state)
+        }
+    }
+
+    #[test]
+    fn reads_exact_overview_window() {
+        let read = |strategy| {
+            let reader = OverviewBandReader::new();
+            let mut buff = vec![0; 4];
+            // Starts half way through the first overview pixel.
+            read_window_into(
+                &ViewBounds::new((0, 0), (2, 2)),
+                &ReadBounds::new((1, 1), (4, 4)),
+                &reader,
+                &[(4, 4)],
+                strategy,
+                &mut buff,
+            )
+            .unwrap();
+            let overview_reads = reader.overview_reads.into_inner().unwrap();
+            assert_eq!(
+                overview_reads,
+                [(Coord { x: 0, y: 0 }, Coord { x: 3, y: 3 })]
+            );
+            buff
+        };
+        // Overview pixels at the centers of the 2x2 overview pixel window at (0.5, 0.5).
+        assert_eq!(read(ReadStrategy::Chunked), [105, 106, 109, 110]);
+        // Overlapping quarters of the 2x2 overview pixels around the centers.
+        assert_eq!(
+            read(ReadStrategy::Interpolated(ResamplingMethod::Average)),
+            [102, 103, 106, 107]
+        );
+    }
+
+    /// 2x2 band holding `1..5`, resampling natively if `native`
+    /// into pixels of 9, recording resampling algorithms.
+    #[derive(Debug)]
+    struct ResamplingBandReader {
+        band: InMemoryBandReader<u16>,
+        native: bool,
+        algorithms: std::sync::Mutex<Vec<GdalResamplingAlgorithm>>,
+    }
+
+    impl BandReader<u16> for ResamplingBandReader {
+        fn raster_size(&self) -> Result<(usize, usize)> {
+            self.band.raster_size()
+        }
+        fn read_into_slice(&self, bounds: &ReadBounds, slice: &mut [u16]) -> Result<()> {
+            self.band.read_into_slice(bounds, slice)
+        }
+        fn read_to_buffer(&self, bounds: &ReadBounds) -> Result<Buffer<u16, 1>> {
+            self.band.read_to_buffer(bounds)
+        }
+        fn read_pixel(&self, offset: Coord<usize>) -> Result<u16> {
+            self.band.read_pixel(offset)
+        }
+        fn read_into_slice_resampled(
+            &self,
+            _bounds: &ReadBounds,
+            _out_shape: (usize, usize),
+            slice: &mut [u16],
+            algorithm: GdalResamplingAlgorithm,
+        ) -> Result<()> {
+            self.algorithms.lock().unwrap().push(algorithm);
+            slice.fill(9);
+            Ok(())
+        }
+        fn resamples_natively(&self) -> bool {
+            self.native
+        }
+        fn hash_source(&self, state: &mut dyn std::hash::Hasher) {
+            self.band.hash_source(state)
+        }
+    }
+
+    #[test]
+    fn default_strategy_resamples_natively() {
+        let read = |native, strategy| {
+            let reader = ResamplingBandReader {
+                band: InMemoryBandReader::new(vec![1, 2, 3, 4], (2, 2)).unwrap(),
+                native,
+                algorithms: Default::default(),
+            };
+            let mut buff = vec![0; 16];
+            read_window_into(
+                &ViewBounds::new((0, 0), (4, 4)),
+                &ReadBounds::new((0, 0), (2, 2)),
+                &reader,
+                &[],
+                strategy,
+                &mut buff,
+            )
+            .unwrap();
+            (buff, reader.algorithms.into_inner().unwrap())
+        };
+        let (buff, algorithms) = read(true, ReadStrategy::default());
+        assert_eq!(buff, [9; 16]);
+        assert_eq!(algorithms, [GdalResamplingAlgorithm::NearestNeighbour]);
+        // Engines without a native resampler, or the chunked strategy,
+        // fill view pixels with the band pixel covering them.
+        let chunked = [1, 1, 2, 2, 1, 1, 2, 2, 3, 3, 4, 4, 3, 3, 4, 4];
+        for (native, strategy) in [
+            (false, ReadStrategy::default()),
+            (true, ReadStrategy::Chunked),
+        ] {
+            let (buff, algorithms) = read(native, strategy);
+            assert_eq!(buff, chunked);
+            assert!(algorithms.is_empty());
+        }
+    }
+
+    #[test]
+    fn selects_coarsest_sufficient_overview() {
+        let overviews = [(50, 50), (25, 25), (13, 13)];
+        // 40x40 native pixels into 10x10 view pixels, a factor of 4.
+        let read_bounds = ReadBounds::new((20, 40), (40, 40));
+        let (level, overview_bounds) =
+            select_overview((100, 100), &overviews, &read_bounds, (10, 10)).unwrap();
+        assert_eq!(level, 1);
+        assert_eq!(overview_bounds.offset(), Coord { x: 5, y: 10 });
+        assert_eq!(overview_bounds.shape(), Coord { x: 10, y: 10 });
+        // A factor of 1.5 needs full resolution.
+        let read_bounds = ReadBounds::new((0, 0), (15, 15));
+        assert!(select_overview((100, 100), &overviews, &read_bounds, (10, 10)).is_none());
+    }
+
+    #[test]
+    fn single_thread_matches_parallel_read() {
+        let view = view_5x3().to_send_sync();
+        let single_thread_buff = view
+            .read_with_options(ReadOptions {
+                max_threads: Some(1),
+                ..Default::default()
             })
-            .collect::<Result<Vec<()>>>()?;
-        Ok(buff)
+            .unwrap();
+        assert_eq!(single_thread_buff.as_ref(), view.read().unwrap().as_ref());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn read_async_matches_read() {
+        let view = view_5x3().to_send_sync();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let async_buff = runtime.block_on(view.read_async()).unwrap();
+        let buff = view.read().unwrap();
+        assert_eq!(async_buff.shape(), buff.shape());
+        assert_eq!(async_buff.as_ref(), buff.as_ref());
+    }
+
+    #[test]
+    fn mask_nodata() {
+        let buff = Buffer::from_owned_parts(vec![0u16, 1, 0, 1], [2, 1, 2]);
+        let mask = nodata_mask(&buff, &[Some(0.), None]);
+        assert_eq!(mask.as_ref(), &[true, false, false, false]);
     }
 }
@@ -0,0 +1,144 @@
+use crate::components::DataType;
+
+/// Summary statistics of a band's pixels.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct BandStats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Population standard deviation.
+    pub stddev: f64,
+    /// Number of pixels counted.
+    pub count: usize,
+}
+
+impl BandStats {
+    /// Statistics of `data`, skipping pixels equal to `nodata`.
+    ///
+    /// Without any pixels counted, all but `count` are `NaN`.
+    pub(crate) fn from_band<T: DataType>(data: &[T], nodata: Option<f64>) -> Self {
+        let nodata_val = nodata.and_then(num::cast::<f64, T>);
+        let values = || {
+            data.iter()
+                .filter(move |val| Some(**val) != nodata_val)
+                .map(|val| val.to_f64().unwrap_or_default())
+        };
+        let (count, sum, min, max) = values().fold(
+            (0, 0., f64::INFINITY, f64::NEG_INFINITY),
+            |(count, sum, min, max), val| (count + 1, sum + val, min.min(val), max.max(val)),
+        );
+        if count == 0 {
+            return Self {
+                min: f64::NAN,
+                max: f64::NAN,
+                mean: f64::NAN,
+                stddev: f64::NAN,
+                count,
+            };
+        }
+        let mean = sum / count as f64;
+        let variance = values().map(|val| (val - mean).powi(2)).sum::<f64>() / count as f64;
+        Self {
+            min,
+            max,
+            mean,
+            stddev: variance.sqrt(),
+            count,
+        }
+    }
+}
+
+/// Pixel counts of a band within equal width bins.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Histogram {
+    /// Bin edges, from the lower edge of the first bin
+    /// to the upper edge of the last.
+    pub edges: Vec<f64>,
+    /// Number of pixels in each bin.
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    /// Histogram of `data` in `bins` bins over `range`, skipping pixels equal to `nodata`.
+    ///
+    /// Without `range`, bins span the band min and max.
+    /// Pixels outside `range` are not counted,
+    /// and those on its upper edge fall in the last bin.
+    pub(crate) fn from_band<T: DataType>(
+        data: &[T],
+        bins: usize,
+        range: Option<(f64, f64)>,
+        nodata: Option<f64>,
+    ) -> Self {
+        let (min, max) = range.unwrap_or_else(|| {
+            let stats = BandStats::from_band(data, nodata);
+            (stats.min, stats.max)
+        });
+        let bin_width = (max - min) / bins as f64;
+        let edges = (0..=bins)
+            .map(|edge_idx| min + edge_idx as f64 * bin_width)
+            .collect();
+        let nodata_val = nodata.and_then(num::cast::<f64, T>);
+        let mut counts = vec![0; bins];
+        data.iter()
+            .filter(|val| Some(**val) != nodata_val)
+            .map(|val| val.to_f64().unwrap_or_default())
+            .filter(|val| (min..=max).contains(val))
+            .for_each(|val| {
+                // A zero width range puts every pixel in the first bin.
+                let bin_idx = if bin_width > 0. {
+                    ((val - min) / bin_width) as usize
+                } else {
+                    0
+                };
+                counts[bin_idx.min(bins - 1)] += 1;
+            });
+        Self { edges, counts }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_band() {
+        let stats = BandStats::from_band(&[7u16; 12], None);
+        assert_eq!(stats.min, 7.);
+        assert_eq!(stats.max, 7.);
+        assert_eq!(stats.mean, 7.);
+        assert_eq!(stats.stddev, 0.);
+        assert_eq!(stats.count, 12);
+    }
+
+    #[test]
+    fn skips_nodata() {
+        let stats = BandStats::from_band(&[0i16, 2, 4, 0], Some(0.));
+        assert_eq!(stats.count, 2);
+        assert_eq!(
+            (stats.min, stats.max, stats.mean, stats.stddev),
+            (2., 4., 3., 1.)
+        );
+        assert!(BandStats::from_band(&[0u8], Some(0.)).mean.is_nan());
+    }
+
+    #[test]
+    fn uniform_histogram() {
+        let data: Vec<u16> = (0..1000).collect();
+        let histogram = Histogram::from_band(&data, 10, None, None);
+        assert_eq!(histogram.edges.len(), 11);
+        assert_eq!(histogram.edges[0], 0.);
+        assert_eq!(histogram.edges[10], 999.);
+        let (fewest, most) = histogram
+            .counts
+            .iter()
+            .fold((usize::MAX, 0), |(min, max), count| {
+                (min.min(*count), max.max(*count))
+            });
+        assert!(most - fewest <= 1);
+        assert_eq!(histogram.counts.iter().sum::<usize>(), 1000);
+
+        let histogram = Histogram::from_band(&data, 4, Some((0., 400.)), Some(0.));
+        assert_eq!(histogram.counts, [99, 100, 100, 101]);
+    }
+}
@@ -1,13 +1,19 @@
-use std::rc::Rc;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::{self, Display},
+    hash::{Hash, Hasher},
+    rc::{Rc, Weak},
+};
 
-use geo::{bool_ops::BoolOpsNum, BoundingRect, CoordNum, Rect};
+use geo::{BoundingRect, Coord, CoordNum, LineString, Rect};
 use geo_traits::{GeometryTrait, RectTrait};
 use proj::{Proj, Transform};
 
 use crate::{
     ambassador_remote_traits::{ambassador_impl_GeometryTrait, ambassador_impl_RectTrait},
     errors::Result,
-    intersection::Intersection,
+    intersection::{BoundingUnion, Intersection},
 };
 
 #[derive(thiserror::Error, Debug)]
@@ -18,22 +24,108 @@ pub enum CrsGeometryError {
     ProjCreateError(#[from] proj::ProjCreateError),
 }
 
+/// UTM zone (1 to 60) containing longitude `lon` in degrees.
+pub fn utm_zone(lon: f64) -> u8 {
+    (((lon + 180.) / 6.).floor() as i64 + 1).clamp(1, 60) as u8
+}
+
+/// WGS84 UTM crs (`EPSG:326<zone>` north, `EPSG:327<zone>` south)
+/// containing `lon`, `lat` in degrees.
+pub fn utm_crs(lon: f64, lat: f64) -> String {
+    let hemisphere_code = if lat >= 0. { 326 } else { 327 };
+    format!("EPSG:{hemisphere_code}{:02}", utm_zone(lon))
+}
+
+thread_local! {
+    /// Crs definitions interned by [Crs::new] on this thread,
+    /// dropped once no [Crs] holds them.
+    static INTERNED_CRS: RefCell<HashMap<Box<str>, Weak<str>>> = RefCell::new(HashMap::new());
+}
+
+/// Interned crs definition, e.g. a WKT definition or `"EPSG:32633"`.
+///
+/// Equal definitions share one allocation,
+/// so clones are cheap and equality compares pointers.
+#[derive(Clone, Debug)]
+pub struct Crs(Rc<str>);
+
+impl Crs {
+    pub fn new(definition: &str) -> Self {
+        INTERNED_CRS.with_borrow_mut(|interned| {
+            if let Some(crs) = interned.get(definition).and_then(Weak::upgrade) {
+                return Crs(crs);
+            }
+            // Forget dropped definitions each time the count doubles,
+            // keeping inserts amortized constant time.
+            if interned.len().is_power_of_two() {
+                interned.retain(|_, crs| crs.strong_count() > 0);
+            }
+            let crs: Rc<str> = Rc::from(definition);
+            interned.insert(Box::from(definition), Rc::downgrade(&crs));
+            Crs(crs)
+        })
+    }
+
+    /// Definition the crs was interned from,
+    /// a WKT definition or an authority code such as `"EPSG:32633"`.
+    pub fn as_wkt(&self) -> &str {
+        &self.0
+    }
+
+    /// Identifier shared by all clones of equal definitions.
+    pub fn id(&self) -> usize {
+        Rc::as_ptr(&self.0) as *const u8 as usize
+    }
+}
+
+impl PartialEq for Crs {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Crs {}
+
+impl Hash for Crs {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id().hash(state)
+    }
+}
+
+impl Display for Crs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_wkt())
+    }
+}
+
+impl From<&str> for Crs {
+    fn from(definition: &str) -> Self {
+        Crs::new(definition)
+    }
+}
+
 #[derive(ambassador::Delegate, Shrinkwrap, Debug, Clone)]
 #[delegate(GeometryTrait, target = "geometry")]
 #[delegate(RectTrait, target = "geometry", where = "G: RectTrait")]
 pub struct CrsGeometry<G: GeometryTrait> {
-    crs: Rc<Box<str>>,
+    crs: Crs,
     #[shrinkwrap(main_field)]
     geometry: G,
 }
 
 impl<G: GeometryTrait> CrsGeometry<G> {
-    pub fn new(crs: Rc<Box<str>>, geometry: G) -> Self {
+    pub fn new(crs: impl Into<Crs>, geometry: G) -> Self {
+        let crs = crs.into();
         Self { crs, geometry }
     }
 
     pub fn crs(&self) -> &str {
-        self.crs.as_ref()
+        self.crs.as_wkt()
+    }
+
+    /// Interned [Crs], cheaper to compare than [CrsGeometry::crs].
+    pub fn interned_crs(&self) -> &Crs {
+        &self.crs
     }
 }
 
@@ -44,7 +136,7 @@ where
     pub fn with_crs(mut self, crs: &str) -> std::result::Result<Self, CrsGeometryError> {
         if self.crs().ne(crs) {
             let proj = Proj::new_known_crs(self.crs(), crs, None)?;
-            self.crs = Rc::new(Box::from(crs));
+            self.crs = Crs::new(crs);
             self.geometry.transform(&proj)?;
         }
         Ok(self)
@@ -68,19 +160,146 @@ where
     pub fn bounding_rect(&self) -> Option<CrsGeometry<Rect<G::T>>> {
         let geometry = self.geometry.bounding_rect().into()?;
         Some(CrsGeometry {
-            crs: Rc::clone(&self.crs),
+            crs: self.crs.clone(),
             geometry,
         })
     }
 }
 
-impl<G: GeometryTrait + Intersection> Intersection for CrsGeometry<G>
-where
-    G::T: BoolOpsNum,
-{
-    type Output = CrsGeometry<G::Output>;
+/// Points along each edge of a rect reprojected by [CrsGeometry::projected_rect].
+const RECT_EDGE_POINTS: usize = 21;
+
+impl CrsGeometry<Rect> {
+    /// Bounding rect of the rect reprojected to `crs`, from points along
+    /// its edges as they may curve in `crs`. Clones if crs is same.
+    pub fn projected_rect(&self, crs: &str) -> std::result::Result<Rect, CrsGeometryError> {
+        if self.crs().eq(crs) {
+            return Ok(self.geometry);
+        }
+        let proj = Proj::new_known_crs(self.crs(), crs, None)?;
+        let (min, max) = (self.geometry.min(), self.geometry.max());
+        let steps = RECT_EDGE_POINTS - 1;
+        let lerp =
+            |from: f64, to: f64, step: usize| from + (to - from) * step as f64 / steps as f64;
+        let ring: LineString = (0..steps)
+            .flat_map(|step| {
+                [
+                    (lerp(min.x, max.x, step), min.y),
+                    (max.x, lerp(min.y, max.y, step)),
+                    (lerp(max.x, min.x, step), max.y),
+                    (min.x, lerp(max.y, min.y, step)),
+                ]
+            })
+            .map(Coord::from)
+            .collect();
+        Ok(ring
+            .transformed(&proj)?
+            .bounding_rect()
+            .expect("ring has points"))
+    }
+}
+
+/// `rhs` in another crs is reprojected to the crs of `self` first,
+/// see [CrsGeometry::projected_rect], which is also the crs of the output.
+impl Intersection for CrsGeometry<Rect> {
+    type Output = CrsGeometry<Rect>;
     fn intersection(&self, rhs: &Self) -> Result<Self::Output> {
-        let geometry = self.geometry.intersection(&rhs.geometry)?;
-        Ok(CrsGeometry::new(Rc::clone(&self.crs), geometry))
+        let geometry = if self.crs == rhs.crs {
+            self.geometry.intersection(&rhs.geometry)?
+        } else {
+            self.geometry
+                .intersection(&rhs.projected_rect(self.crs())?)?
+        };
+        Ok(CrsGeometry::new(self.crs.clone(), geometry))
+    }
+}
+
+/// Like [Intersection], reprojecting `rhs` in another crs to the crs of `self`.
+impl BoundingUnion for CrsGeometry<Rect> {
+    type Output = CrsGeometry<Rect>;
+    fn bounding_union(&self, rhs: &Self) -> Result<Self::Output> {
+        let geometry = if self.crs == rhs.crs {
+            self.geometry.bounding_union(&rhs.geometry)?
+        } else {
+            self.geometry
+                .bounding_union(&rhs.projected_rect(self.crs())?)?
+        };
+        Ok(CrsGeometry::new(self.crs.clone(), geometry))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forgets_dropped_crs() {
+        let kept = Crs::new("EPSG:4326");
+        for zone in 1..=60 {
+            Crs::new(&utm_crs(zone as f64 * 6. - 183., 0.));
+        }
+        INTERNED_CRS.with_borrow(|interned| assert!(interned.len() < 60));
+        assert_eq!(Crs::new("EPSG:4326"), kept);
+    }
+
+    #[test]
+    fn utm() {
+        assert_eq!(utm_zone(-180.), 1);
+        assert_eq!(utm_zone(13.4), 33);
+        assert_eq!(utm_zone(180.), 60);
+        assert_eq!(utm_crs(13.4, 11.7), "EPSG:32633");
+        assert_eq!(utm_crs(-58.4, -34.6), "EPSG:32721");
+    }
+
+    #[test]
+    fn interned_crs() {
+        let crs = Crs::new("EPSG:32633");
+        assert_eq!(crs, Crs::new("EPSG:32633"));
+        assert_eq!(crs.id(), Crs::new("EPSG:32633").id());
+        assert_ne!(crs, Crs::new("EPSG:4326"));
+        assert_eq!(crs.as_wkt(), "EPSG:32633");
+    }
+
+    #[test]
+    fn intersection_reprojects_rhs() {
+        let utm = CrsGeometry::new(
+            Crs::new("EPSG:32633"),
+            Rect::new((300000., 1290000.), (310000., 1300000.)),
+        );
+        // Covers the west half of `utm`.
+        let lonlat = CrsGeometry::new(Crs::new("EPSG:4326"), Rect::new((13., 11.), (13.2, 12.)));
+        let intersection = utm.intersection(&lonlat).unwrap();
+        assert_eq!(intersection.crs(), "EPSG:32633");
+        assert_eq!(intersection.min(), utm.min());
+        assert!(intersection.max().x < 306000.);
+        assert_eq!(intersection.max().y, utm.max().y);
+    }
+
+    #[test]
+    fn bounding_union_reprojects_rhs() {
+        let utm = CrsGeometry::new(
+            Crs::new("EPSG:32633"),
+            Rect::new((300000., 1290000.), (310000., 1300000.)),
+        );
+        let lonlat = CrsGeometry::new(Crs::new("EPSG:4326"), Rect::new((13., 11.), (13.2, 12.)));
+        let union = utm.bounding_union(&lonlat).unwrap();
+        assert_eq!(union.crs(), "EPSG:32633");
+        let lonlat_utm = lonlat.projected_rect("EPSG:32633").unwrap();
+        assert_eq!(union.min().y, lonlat_utm.min().y);
+        assert_eq!(union.max().y, lonlat_utm.max().y);
+        assert_eq!(union.max().x, utm.max().x);
+    }
+
+    #[test]
+    fn projected_rect_covers_corners() {
+        let (min, max) = ((300000., 1290000.), (310000., 1300000.));
+        let utm = CrsGeometry::new(Crs::new("EPSG:32633"), Rect::new(min, max));
+        let lonlat = utm.projected_rect("EPSG:4326").unwrap();
+        let proj = Proj::new_known_crs("EPSG:32633", "EPSG:4326", None).unwrap();
+        for corner in [min, (max.0, min.1), max, (min.0, max.1)] {
+            let (lon, lat) = proj.convert(corner).unwrap();
+            assert!((lonlat.min().x..=lonlat.max().x).contains(&lon));
+            assert!((lonlat.min().y..=lonlat.max().y).contains(&lat));
+        }
     }
 }
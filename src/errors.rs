@@ -1,10 +1,21 @@
 pub type Result<T> = std::result::Result<T, RusterioError>;
 
+/// Crate error.
+///
+/// Variants wrapping another error either forward it with `#[error(transparent)]`
+/// or carry it with `#[source]`/`#[from]`, so the cause stays
+/// reachable through [std::error::Error::source].
 #[derive(thiserror::Error, Debug)]
 pub enum RusterioError {
     /// lib errors
     #[error(transparent)]
     GdalError(#[from] gdal::errors::GdalError),
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    GlobPatternError(#[from] glob::PatternError),
+    #[error(transparent)]
+    GlobError(#[from] glob::GlobError),
     /// crate mod errors
     #[error(transparent)]
     CrsGeometryError(#[from] crate::crs_geo::CrsGeometryError),
@@ -15,6 +26,100 @@ pub enum RusterioError {
     /// crate lib errors
     #[error("Value could not be cast")]
     Uncastable,
+    #[error("Value {value_debug} could not be cast to {target_type}")]
+    UncastableValue {
+        value_debug: String,
+        target_type: &'static str,
+    },
     #[error("Coundn't find area of use in file")]
     NoAreaOfUse,
+    #[error("Could not write {}", path.display())]
+    WriteError {
+        path: std::path::PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("No crs found in file {}", path.display())]
+    NoCrsFound { path: std::path::PathBuf },
+    #[error("Unsupported gdal driver {0}")]
+    UnsupportedDriver(String),
+    #[error("Read window at offset {offset:?} with shape {shape:?} is out of range of raster with shape {raster_size:?}")]
+    ReadBoundsOutOfRange {
+        offset: (usize, usize),
+        shape: (usize, usize),
+        raster_size: (usize, usize),
+    },
+    #[error("Bounds are {distance} pixels off the pixel grid")]
+    BoundsNotAligned { distance: f64 },
+    #[error("Affine transform is not invertible")]
+    NonInvertibleTransform,
+    #[error("Can not compute {requested} components from {channels} channels")]
+    InvalidComponentCount { requested: usize, channels: usize },
+    #[error("Invalid scale factor {0}")]
+    InvalidFactor(usize),
+    #[error("Invalid pixel size {0}")]
+    InvalidPixelSize(f64),
+    #[error("Invalid tile shape {0:?}")]
+    InvalidTileShape((usize, usize)),
+    #[error("Invalid histogram bin count {0}")]
+    InvalidBinCount(usize),
+    #[error("Invalid formula: {0}")]
+    InvalidFormula(String),
+    #[error("Invalid wkt: {0}")]
+    InvalidWkt(String),
+    #[error("Expected {expected} bands, found {found}")]
+    BandCountMismatch { expected: usize, found: usize },
+    #[error("No files matching {pattern} in {}", dir.display())]
+    NoFilesMatched {
+        dir: std::path::PathBuf,
+        pattern: String,
+    },
+    #[error("No bands selected")]
+    NoBandsSelected,
+    #[error("Band not found: {0}")]
+    BandNotFound(String),
+    #[error("Expected shape {expected:?}, found {found:?}")]
+    ShapeMismatch {
+        expected: Vec<usize>,
+        found: Vec<usize>,
+    },
+    #[error("Expected crs {expected}, found {found}")]
+    CrsMismatch { expected: String, found: String },
+    #[error("Expected pixel size {expected:?}, found {found:?}")]
+    ResolutionMismatch {
+        expected: (f64, f64),
+        found: (f64, f64),
+    },
+    #[error("Band has no overview at level {0}")]
+    OverviewNotFound(usize),
+    #[error(transparent)]
+    ThreadPoolBuildError(#[from] rayon::ThreadPoolBuildError),
+    #[cfg(feature = "tokio")]
+    #[error(transparent)]
+    TaskJoinError(#[from] tokio::task::JoinError),
+    #[cfg(feature = "image")]
+    #[error(transparent)]
+    ImageError(#[from] image::ImageError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn source_chain_is_walkable() {
+        let err = RusterioError::WriteError {
+            path: std::path::PathBuf::from("out.npy"),
+            source: std::io::Error::from(std::io::ErrorKind::PermissionDenied),
+        };
+        let source = err.source().unwrap();
+        assert_eq!(
+            source.downcast_ref::<std::io::Error>().unwrap().kind(),
+            std::io::ErrorKind::PermissionDenied
+        );
+        let chain: Vec<&dyn Error> =
+            std::iter::successors(Some(&err as &dyn Error), |&err| err.source()).collect();
+        assert_eq!(chain.len(), 2);
+    }
 }
@@ -2,17 +2,27 @@ use std::{collections::HashSet, hash::RandomState, rc::Rc};
 
 use itertools::Itertools;
 
+use crate::errors::{Result, RusterioError};
+
 #[derive(Clone, serde::Deserialize, serde::Serialize)]
 pub struct Indexes {
     selection: Rc<[usize]>,
     drop: bool,
+    /// Band names to select, resolved into `selection`
+    /// by [Indexes::resolve_names].
+    #[serde(default)]
+    names: Option<Rc<[String]>>,
 }
 
 impl<const N: usize> From<([usize; N], bool)> for Indexes {
     fn from(value: ([usize; N], bool)) -> Self {
         let selection = Rc::from(value.0);
         let drop = value.1;
-        Indexes { selection, drop }
+        Indexes {
+            selection,
+            drop,
+            names: None,
+        }
     }
 }
 
@@ -20,7 +30,11 @@ impl From<(std::ops::Range<usize>, bool)> for Indexes {
     fn from(value: (std::ops::Range<usize>, bool)) -> Self {
         let selection = value.0.collect();
         let drop = value.1;
-        Indexes { selection, drop }
+        Indexes {
+            selection,
+            drop,
+            names: None,
+        }
     }
 }
 
@@ -30,6 +44,17 @@ impl<const N: usize> From<[usize; N]> for Indexes {
         Indexes {
             selection,
             drop: false,
+            names: None,
+        }
+    }
+}
+
+impl From<Vec<usize>> for Indexes {
+    fn from(value: Vec<usize>) -> Self {
+        Indexes {
+            selection: Rc::from(value),
+            drop: false,
+            names: None,
         }
     }
 }
@@ -40,12 +65,14 @@ impl From<std::ops::Range<usize>> for Indexes {
         Indexes {
             selection,
             drop: false,
+            names: None,
         }
     }
 }
 
 impl Indexes {
     pub fn indexes_from(self, collection_len: usize) -> Rc<[usize]> {
+        debug_assert!(self.names.is_none(), "band names were not resolved");
         let idxs = self.selection;
         if self.drop {
             let drop_idxs: HashSet<usize, RandomState> =
@@ -61,6 +88,39 @@ impl Indexes {
         }
     }
 
+    /// Select from `total` bands with Python-like indexes,
+    /// where negative indexes count from the last band (`-1`).
+    pub fn from_signed(selection: &[isize], total: usize) -> Result<Indexes> {
+        let selection = selection
+            .iter()
+            .map(|&idx| {
+                let resolved = if idx < 0 {
+                    total.checked_sub(idx.unsigned_abs())
+                } else {
+                    Some(idx.unsigned_abs())
+                };
+                resolved.filter(|idx| *idx < total).ok_or_else(|| {
+                    RusterioError::BandNotFound(format!("index {idx} of {total} bands"))
+                })
+            })
+            .collect::<Result<Rc<[usize]>>>()?;
+        Ok(Indexes {
+            selection,
+            drop: false,
+            names: None,
+        })
+    }
+
+    /// Check all selected indexes are within `total_bands`.
+    pub fn validate(&self, total_bands: usize) -> Result<()> {
+        match self.selection.iter().find(|idx| **idx >= total_bands) {
+            Some(idx) => Err(RusterioError::BandNotFound(format!(
+                "index {idx} of {total_bands} bands"
+            ))),
+            None => Ok(()),
+        }
+    }
+
     pub fn select_from<T: Clone + Copy>(self, collection: Vec<T>) -> Box<[T]> {
         self.indexes_from(collection.len())
             .iter()
@@ -72,6 +132,83 @@ impl Indexes {
         Self {
             selection: Rc::from([]),
             drop: true,
+            names: None,
         }
     }
+
+    /// Select bands by name, e.g. `["B04", "B03", "B02"]`, in the given order.
+    pub fn by_name<S: Into<String>>(names: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            selection: Rc::from([]),
+            drop: false,
+            names: Some(names.into_iter().map(Into::into).collect()),
+        }
+    }
+
+    /// Resolve names selected with [Indexes::by_name] into positions
+    /// within `band_names`, which is only called if there are any.
+    pub fn resolve_names(
+        self,
+        band_names: impl FnOnce() -> Result<Vec<String>>,
+    ) -> Result<Indexes> {
+        let Some(names) = self.names else {
+            return Ok(self);
+        };
+        let band_names = band_names()?;
+        let selection = names
+            .iter()
+            .map(|name| {
+                band_names
+                    .iter()
+                    .position(|band_name| band_name.eq(name))
+                    .ok_or_else(|| {
+                        RusterioError::BandNotFound(format!(
+                            "{name}, available bands: {}",
+                            band_names.join(", ")
+                        ))
+                    })
+            })
+            .collect::<Result<Rc<[usize]>>>()?;
+        Ok(Indexes {
+            selection,
+            drop: false,
+            names: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn from_signed() {
+        let indexes = Indexes::from_signed(&[-1, 0, -3], 3).unwrap();
+        assert_eq!(indexes.indexes_from(3).as_ref(), &[2, 0, 0]);
+        assert!(Indexes::from_signed(&[-4], 3).is_err());
+        assert!(Indexes::from_signed(&[3], 3).is_err());
+    }
+
+    #[test]
+    fn by_name() {
+        let band_names = || Ok(vec!["B02".into(), "B03".into(), "B04".into()]);
+        let indexes = Indexes::by_name(["B04", "B03", "B02"])
+            .resolve_names(band_names)
+            .unwrap();
+        assert_eq!(indexes.indexes_from(3).as_ref(), &[2, 1, 0]);
+        match Indexes::by_name(["B08"]).resolve_names(band_names) {
+            Err(RusterioError::BandNotFound(msg)) => assert!(msg.contains("B02, B03, B04")),
+            _ => panic!("expected BandNotFound"),
+        }
+    }
+
+    #[rstest]
+    #[case(Indexes::from([0, 2]), true)]
+    #[case(Indexes::from([0, 3]), false)]
+    #[case(Indexes::from(([3], true)), false)]
+    #[case(Indexes::all(), true)]
+    fn validate(#[case] indexes: Indexes, #[case] valid: bool) {
+        assert_eq!(indexes.validate(3).is_ok(), valid);
+    }
 }
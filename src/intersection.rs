@@ -17,6 +17,12 @@ pub trait Intersection {
     fn intersection(&self, rhs: &Self) -> Result<Self::Output>;
 }
 
+/// Dual of [Intersection], smallest geometry containing both.
+pub trait BoundingUnion {
+    type Output: GeometryTrait;
+    fn bounding_union(&self, rhs: &Self) -> Result<Self::Output>;
+}
+
 impl<T: CoordNum> Intersection for Rect<T> {
     type Output = Rect<T>;
     fn intersection(&self, rhs: &Self) -> Result<Rect<T>> {
@@ -39,9 +45,43 @@ impl<T: CoordNum> Intersection for Rect<T> {
     }
 }
 
+impl<T: CoordNum> BoundingUnion for Rect<T> {
+    type Output = Rect<T>;
+    fn bounding_union(&self, rhs: &Self) -> Result<Rect<T>> {
+        let min = self
+            .min()
+            .operate(&rhs.min(), |x, y| if x < y { x } else { y });
+        let max = self
+            .max()
+            .operate(&rhs.max(), |x, y| if x > y { x } else { y });
+        Ok(Self::new(min, max))
+    }
+}
+
 impl<T: CoordNum + BoolOpsNum + Ord> Intersection for Polygon<T> {
     type Output = MultiPolygon<T>;
     fn intersection(&self, rhs: &Self) -> Result<MultiPolygon<T>> {
         Ok(<Self as BooleanOps>::intersection(&self, rhs))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rect_bounding_union() {
+        let lhs = Rect::new((0., 0.), (2., 1.));
+        let rhs = Rect::new((1., -1.), (3., 0.5));
+        assert_eq!(
+            lhs.bounding_union(&rhs).unwrap(),
+            Rect::new((0., -1.), (3., 1.))
+        );
+        // Disjoint rects are covered too.
+        let far = Rect::new((10., 10.), (11., 11.));
+        assert_eq!(
+            lhs.bounding_union(&far).unwrap(),
+            Rect::new((0., 0.), (11., 11.))
+        );
+    }
+}
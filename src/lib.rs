@@ -13,17 +13,32 @@ mod intersection;
 use geo::{Coord, CoordNum, Line, MapCoords};
 use geo_traits::{CoordTrait, LineTrait};
 
-pub use buffer::Buffer;
+#[cfg(feature = "npy")]
+pub use buffer::NpyDataType;
+pub use buffer::{Buffer, Stretch};
 pub use components::{
-    bounds::{Bounds, ViewBounds},
-    engines::gdal_engine,
-    raster::Raster,
-    view::{InfoView, ReadView},
+    band::RadiometricInfo,
+    bounds::{Bounds, GeoBounds, ViewBounds},
+    cache::BlockCache,
+    engines::{gdal_engine, mem_engine::MemoryFile},
+    mosaic::BlendMode,
+    raster::{
+        group::{BandGroupSummary, BandSummary},
+        Raster,
+    },
+    resampling::{GdalResamplingAlgorithm, ReadStrategy, ResamplingMethod},
+    view::{
+        band::BandSnapshot,
+        masked::MaskedView,
+        statistics::{BandStats, Histogram},
+        ReadOptions, SendSyncView, View,
+    },
     DataType,
 };
-pub use crs_geo::CrsGeometry;
+pub use crs_geo::{Crs, CrsGeometry};
 use errors::{Result, RusterioError};
 pub use indexes::Indexes;
+pub use intersection::BoundingUnion;
 
 trait CoordUtils: CoordTrait + Sized {
     /* fn map<NT: CoordNum>(self, func: impl Fn(Coord<Self::T>) -> Coord<NT>) -> Coord<NT>
@@ -51,11 +66,11 @@ trait CoordUtils: CoordTrait + Sized {
 
     fn try_cast<NT: CoordNum + num::NumCast>(self) -> Result<Coord<NT>>
     where
-        Self::T: num::NumCast,
+        Self::T: num::NumCast + Copy + std::fmt::Debug,
     {
         Ok(Coord {
-            x: try_cast(self.x())?,
-            y: try_cast(self.y())?,
+            x: try_cast_value(self.x())?,
+            y: try_cast_value(self.y())?,
         })
     }
 
@@ -89,6 +104,14 @@ fn try_cast<T: num::NumCast, U: num::NumCast>(val: T) -> Result<U> {
     num_traits::cast(val).ok_or(RusterioError::Uncastable)
 }
 
+/// Like [try_cast], but the error reports the value and target type.
+fn try_cast_value<T: num::NumCast + Copy + std::fmt::Debug, U: num::NumCast>(val: T) -> Result<U> {
+    num_traits::cast(val).ok_or_else(|| RusterioError::UncastableValue {
+        value_debug: format!("{val:?}"),
+        target_type: std::any::type_name::<U>(),
+    })
+}
+
 fn try_tuple_cast<T: num::NumCast, U: num::NumCast>(tuple: (T, T)) -> Result<(U, U)> {
     Ok((try_cast(tuple.0)?, try_cast(tuple.1)?))
 }
@@ -96,11 +119,15 @@ fn try_tuple_cast<T: num::NumCast, U: num::NumCast>(tuple: (T, T)) -> Result<(U,
 #[cfg(test)]
 mod tests {
 
-    use crate::components::{bounds::ViewBounds, engines::gdal_engine::GdalFile};
+    use crate::components::{
+        bounds::{GeoBounds, ViewBounds},
+        engines::gdal_engine::{GdalFile, GdalFileBuilder, OpenOptions},
+        file::File,
+    };
 
     use super::*;
+    use geo::Rect;
     use log::info;
-    use ndarray::Axis;
     use rstest::rstest;
 
     const SENTINEL2_FILE_NAME: &str =
@@ -152,6 +179,36 @@ mod tests {
         info!("{:#?}", sentinel_raster);
     }
 
+    #[rstest]
+    #[test_log::test]
+    fn groups_of_full_sentinel2() {
+        let sentinel_raster = gdal_engine::open::<u16>(SENTINEL2_FILE_PATH()).unwrap();
+        let resolutions: Vec<(f64, f64)> = sentinel_raster
+            .list_band_groups()
+            .unwrap()
+            .into_iter()
+            .map(|group| group.resolution_m)
+            .collect();
+        assert_eq!(resolutions, [(10., 10.), (20., 20.), (60., 60.)]);
+    }
+
+    #[rstest]
+    #[test_log::test]
+    fn crs_matches_gdal() {
+        let path = SENTINEL2_RESOLUTION_GROUP_PATH(10);
+        let projection = gdal::Dataset::open(&path).unwrap().projection();
+        let raster = Raster::new::<GdalFile<u16>>(&path, Indexes::all()).unwrap();
+        assert_eq!(raster.crs(), projection);
+        assert!(raster.crs().contains("UTM zone 33N"));
+    }
+
+    #[rstest]
+    #[test_log::test]
+    fn works_with_safe_product() {
+        let sentinel_raster = Raster::<u16>::open_safe_product(SENTINEL2_FILE_PATH()).unwrap();
+        info!("{:#?}", sentinel_raster);
+    }
+
     #[rstest]
     #[test_log::test]
     fn works_with_partial_sentinel2() {
@@ -160,6 +217,402 @@ mod tests {
         info!("{:#?}", sentinel_raster);
     }
 
+    #[rstest]
+    #[case(10)]
+    #[case(20)]
+    #[case(60)]
+    #[test_log::test]
+    fn opens_resolution_group(#[case] resolution: u32) {
+        let raster =
+            gdal_engine::open_resolution::<u16>(SENTINEL2_FILE_PATH(), resolution).unwrap();
+        let groups = raster.list_band_groups().unwrap();
+        assert_eq!(groups.len(), 1);
+        let resolution = resolution as f64;
+        assert_eq!(groups[0].resolution_m, (resolution, resolution));
+    }
+
+    #[rstest]
+    #[test_log::test]
+    fn missing_resolution_group() {
+        let raster = gdal_engine::open_resolution::<u16>(SENTINEL2_FILE_PATH(), 30);
+        assert!(matches!(
+            raster,
+            Err(RusterioError::GdalEngineError(
+                gdal_engine::GdalEngineError::ResolutionGroupNotFound { .. }
+            ))
+        ));
+    }
+
+    #[rstest]
+    #[test_log::test]
+    fn from_directory_of_geotiffs() {
+        let dir = std::env::temp_dir().join("rusterio_from_directory");
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, value) in [("B02", 2u16), ("B01", 1)] {
+            write_synthetic_geotiff(&dir.join(format!("{name}.tif")), vec![value; 16]);
+        }
+
+        let raster =
+            Raster::<u16>::from_directory(&dir, "*.tif", gdal_engine::SortOrder::Alphabetical);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let raster = raster.unwrap();
+        let band_names: Vec<String> = (0..2)
+            .map(|idx| raster.band_info(idx).unwrap().name())
+            .collect();
+        assert_eq!(band_names, ["B01", "B02"]);
+        let buff = raster.view(None, Indexes::all()).unwrap().read().unwrap();
+        assert_eq!(&buff.as_ref()[..16], [1; 16]);
+        assert_eq!(&buff.as_ref()[16..], [2; 16]);
+    }
+
+    /// Write `data` as a single band 4x4 GeoTIFF of `T` at `path`,
+    /// of 10m pixels in `EPSG:32633` with top left corner at (300000, 1300040).
+    pub(crate) fn write_synthetic_geotiff<T: gdal_engine::GdalDataType>(
+        path: &std::path::Path,
+        data: Vec<T>,
+    ) {
+        let bounds = GeoBounds::from(CrsGeometry::new(
+            "EPSG:32633",
+            Rect::new((300000., 1300000.), (300040., 1300040.)),
+        ));
+        let buffer = Buffer::from_owned_parts(data, [1, 4, 4]);
+        gdal_engine::write_geotiff(path, &buffer, &bounds, &[String::from("band")]).unwrap();
+    }
+
+    /// Read the center 2x2 window of a synthetic GeoTIFF holding `0..16` as `T`.
+    fn read_synthetic_window<T: gdal_engine::GdalDataType>() -> Vec<f64> {
+        let path =
+            std::env::temp_dir().join(format!("rusterio_{}.tif", std::any::type_name::<T>()));
+        let data = (0..16u8).map(|val| num::cast(val).unwrap()).collect();
+        write_synthetic_geotiff::<T>(&path, data);
+        let buff = Raster::new::<GdalFile<T>>(&path, Indexes::all()).and_then(|raster| {
+            raster
+                .view(None, Indexes::all())?
+                .clip(ViewBounds::new((1, 1), (2, 2)))?
+                .read()
+        });
+        std::fs::remove_file(&path).unwrap();
+        buff.unwrap()
+            .as_ref()
+            .iter()
+            .map(|val| val.to_f64().unwrap())
+            .collect()
+    }
+
+    #[rstest]
+    #[test_log::test]
+    fn geo_transform_matches_gdal() {
+        let path = std::env::temp_dir().join("rusterio_geo_transform.tif");
+        write_synthetic_geotiff::<u8>(&path, vec![0; 16]);
+        let gdal_transform = gdal::Dataset::open(&path).unwrap().geo_transform().unwrap();
+        let raster = Raster::new::<GdalFile<u8>>(&path, Indexes::all());
+        std::fs::remove_file(&path).unwrap();
+        let raster = raster.unwrap();
+        assert_eq!(raster.geo_transform().unwrap(), gdal_transform);
+        let view = raster.view(None, Indexes::all()).unwrap();
+        assert_eq!(view.geo_transform(), gdal_transform);
+    }
+
+    #[rstest]
+    #[test_log::test]
+    fn rereads_rewritten_file() {
+        let path = std::env::temp_dir().join("rusterio_rewritten.tif");
+        let read = |path: &std::path::Path| {
+            Raster::new::<GdalFile<u8>>(path, Indexes::all())?
+                .view(None, Indexes::all())?
+                .read()
+        };
+        write_synthetic_geotiff::<u8>(&path, vec![1; 16]);
+        let before = read(&path);
+        write_synthetic_geotiff::<u8>(&path, vec![2; 16]);
+        // Make sure the rewrite is seen as a modification on coarse clocks.
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(1))
+            .unwrap();
+        let after = read(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(before.unwrap().as_ref(), [1; 16]);
+        assert_eq!(after.unwrap().as_ref(), [2; 16]);
+    }
+
+    #[rstest]
+    #[test_log::test]
+    fn bounds_to_ewkt() {
+        let path = std::env::temp_dir().join("rusterio_bounds_ewkt.tif");
+        write_synthetic_geotiff::<u8>(&path, vec![0; 16]);
+        let raster = Raster::new::<GdalFile<u8>>(&path, Indexes::all());
+        std::fs::remove_file(&path).unwrap();
+        // Raster crs is the WKT GDAL reads, not an `EPSG:<code>` identifier.
+        let ewkt = raster.unwrap().bounds().to_ewkt().unwrap();
+        assert!(ewkt.starts_with("SRID=32633;POLYGON(("));
+    }
+
+    #[rstest]
+    #[test_log::test]
+    fn opens_generic_geotiff() {
+        let path = std::env::temp_dir().join("rusterio_generic.tif");
+        write_synthetic_geotiff::<u8>(&path, vec![0; 16]);
+        let raster = gdal_engine::open::<u8>(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(raster.unwrap().band_names().len(), 1);
+    }
+
+    #[rstest]
+    #[test_log::test]
+    fn open_without_crs() {
+        use gdal::DriverManager;
+
+        // GeoTIFF without a crs, which no sensor detects.
+        let path = std::env::temp_dir().join("rusterio_no_crs.tif");
+        DriverManager::get_driver_by_name("GTiff")
+            .unwrap()
+            .create_with_band_type::<u8, _>(&path, 4, 4, 1)
+            .unwrap()
+            .set_geo_transform(&[300000., 10., 0., 1300040., 0., -10.])
+            .unwrap();
+        let opened = gdal_engine::open::<u8>(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(opened, Err(RusterioError::NoCrsFound { .. })));
+    }
+
+    #[rstest]
+    #[case::u8(read_synthetic_window::<u8>)]
+    #[case::i8(read_synthetic_window::<i8>)]
+    #[case::u16(read_synthetic_window::<u16>)]
+    #[case::i16(read_synthetic_window::<i16>)]
+    #[case::u32(read_synthetic_window::<u32>)]
+    #[case::i32(read_synthetic_window::<i32>)]
+    #[case::f32(read_synthetic_window::<f32>)]
+    #[case::f64(read_synthetic_window::<f64>)]
+    #[test_log::test]
+    fn works_with_synthetic_geotiff(#[case] read_window: fn() -> Vec<f64>) {
+        assert_eq!(read_window(), vec![5., 6., 9., 10.]);
+    }
+
+    #[rstest]
+    #[test_log::test]
+    fn geotiff_round_trip() {
+        let path = std::env::temp_dir().join("rusterio_round_trip_source.tif");
+        write_synthetic_geotiff::<u16>(&path, (0..16).collect());
+        let raster = Raster::new::<GdalFile<u16>>(&path, Indexes::all()).unwrap();
+        let view = raster
+            .view(None, Indexes::all())
+            .unwrap()
+            .clip(ViewBounds::new((1, 1), (2, 3)))
+            .unwrap();
+        let (geo_bounds, band_names) = (view.geo_bounds().clone(), view.band_names());
+        let buff = view.read().unwrap();
+
+        let out_path = std::env::temp_dir().join("rusterio_round_trip.tif");
+        gdal_engine::write_geotiff(&out_path, &buff, &geo_bounds, &band_names).unwrap();
+        let written = Raster::new::<GdalFile<u16>>(&out_path, Indexes::all()).and_then(|written| {
+            let written_buff = written.view(None, Indexes::all())?.read()?;
+            Ok((written.bounds().clone(), written_buff))
+        });
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&out_path).unwrap();
+        let (written_bounds, written_buff) = written.unwrap();
+        assert_eq!(written_bounds.min(), geo_bounds.min());
+        assert_eq!(written_bounds.max(), geo_bounds.max());
+        assert_eq!(written_buff.shape(), [1, 3, 2]);
+        assert_eq!(written_buff.as_ref(), buff.as_ref());
+    }
+
+    #[rstest]
+    #[test_log::test]
+    fn discovers_overviews() {
+        let path = std::env::temp_dir().join("rusterio_overviews.tif");
+        write_synthetic_geotiff::<u16>(&path, (0..16).collect());
+        gdal::Dataset::open(&path)
+            .unwrap()
+            .build_overviews("NEAREST", &[2, 4], &[])
+            .unwrap();
+        let overviews = Raster::new::<GdalFile<u16>>(&path, Indexes::all()).and_then(|raster| {
+            let band_info = raster.band_info(0)?;
+            (0..band_info.overview_count()?)
+                .map(|level| band_info.overview_size(level))
+                .collect::<Result<Vec<_>>>()
+        });
+        // Overviews of datasets opened read only go to an external `.ovr` file.
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(format!("{}.ovr", path.display())).unwrap();
+        assert_eq!(overviews.unwrap(), [(2, 2), (1, 1)]);
+    }
+
+    #[rstest]
+    #[test_log::test]
+    fn reprojects_to_wgs84() {
+        let path = std::env::temp_dir().join("rusterio_reproject.tif");
+        write_synthetic_geotiff::<u16>(&path, (0..16).collect());
+        let reprojected = Raster::new::<GdalFile<u16>>(&path, Indexes::all())
+            .and_then(|raster| raster.reproject("EPSG:4326"));
+        std::fs::remove_file(&path).unwrap();
+        let reprojected = reprojected.unwrap();
+        let bounds = reprojected.bounds();
+        assert_eq!(bounds.crs(), "EPSG:4326");
+        // 40m square at UTM 33N (300000, 1300000) lies around 13.16E, 11.75N.
+        assert!((bounds.min().x - 13.16).abs() < 0.05);
+        assert!((bounds.min().y - 11.75).abs() < 0.05);
+        assert!(bounds.width() < 0.001 && bounds.height() < 0.001);
+        assert_eq!(
+            reprojected.num_pixels_per_band(),
+            reprojected
+                .view(None, Indexes::all())
+                .unwrap()
+                .read()
+                .unwrap()
+                .as_ref()
+                .len()
+        );
+    }
+
+    #[rstest]
+    #[test_log::test]
+    fn num_pixels_matches_full_view() {
+        let raster = gdal_engine::open::<u16>(SENTINEL2_RESOLUTION_GROUP_PATH(10)).unwrap();
+        let array_shape = raster.view(None, Indexes::all()).unwrap().array_shape();
+        assert_eq!(raster.num_pixels(), array_shape.iter().product::<usize>());
+        assert_eq!(
+            raster.num_pixels_per_band(),
+            array_shape[1] * array_shape[2]
+        );
+        assert_eq!(raster.estimated_memory_bytes(), raster.num_pixels() * 2);
+    }
+
+    #[rstest]
+    #[test_log::test]
+    fn surfaces_nodata() {
+        let sentinel_raster =
+            gdal_engine::open::<u16>(SENTINEL2_RESOLUTION_GROUP_PATH(10)).unwrap();
+        let view = sentinel_raster
+            .view(None, Indexes::from([0usize]))
+            .unwrap()
+            .clip(ViewBounds::new((0, 0), (10, 10)))
+            .unwrap();
+        assert_eq!(view.nodata(), vec![Some(0.)]);
+        let (buff, mask) = view.read_masked().unwrap();
+        assert_eq!(mask.shape(), buff.shape());
+    }
+
+    #[rstest]
+    #[test_log::test]
+    fn view_from_lonlat_bounds() {
+        let raster = gdal_engine::open::<u16>(SENTINEL2_RESOLUTION_GROUP_PATH(10)).unwrap();
+        // 1km square, 100x100 pixels at 10m.
+        let (min, max) = (raster.bounds().min(), raster.bounds().max());
+        let utm_bounds = GeoBounds::from(CrsGeometry::new(
+            raster.bounds().crs(),
+            Rect::new(
+                (min.x + 1000., max.y - 2000.),
+                (min.x + 2000., max.y - 1000.),
+            ),
+        ));
+        let lonlat = utm_bounds.projected_geometry("EPSG:4326").unwrap();
+        let lonlat_bounds = GeoBounds::from_lonlat(lonlat.min().x_y(), lonlat.max().x_y());
+        let view = raster
+            .view(Some(lonlat_bounds), Indexes::from([0usize]))
+            .unwrap();
+        let (width, height) = view.bounds_shape();
+        assert!(width.abs_diff(100) <= 1 && height.abs_diff(100) <= 1);
+    }
+
+    #[rstest]
+    #[test_log::test]
+    fn view_reprojects_bounds() {
+        let raster = gdal_engine::open::<u16>(SENTINEL2_RESOLUTION_GROUP_PATH(10)).unwrap();
+        let utm_bounds = raster.bounds().pad(-10000.);
+        let lonlat_bounds = utm_bounds.clone().with_crs("EPSG:4326").unwrap();
+        let view = raster.view(Some(lonlat_bounds), Indexes::all()).unwrap();
+        assert_eq!(view.geo_bounds().crs(), raster.crs());
+        let center = (utm_bounds.min() + utm_bounds.max()) / 2.;
+        let (min, max) = (view.geo_bounds().min(), view.geo_bounds().max());
+        assert!((min.x..max.x).contains(&center.x));
+        assert!((min.y..max.y).contains(&center.y));
+    }
+
+    #[rstest]
+    #[test_log::test]
+    fn view_exposes_band_info() {
+        let raster = gdal_engine::open::<u16>(SENTINEL2_RESOLUTION_GROUP_PATH(10)).unwrap();
+        let view = raster.view(None, Indexes::from([2usize, 0])).unwrap();
+        let band_names = raster.band_names();
+        assert_eq!(
+            view.band_names(),
+            vec![band_names[2].clone(), band_names[0].clone()]
+        );
+        assert!(view.band_metadata(1).is_ok());
+        assert!(matches!(
+            view.band_metadata(2),
+            Err(RusterioError::BandNotFound(_))
+        ));
+    }
+
+    #[rstest]
+    #[test_log::test]
+    fn parallel_bands_match_serial() {
+        let open = |parallel| {
+            let file = GdalFileBuilder::<u16>::new(SENTINEL2_RESOLUTION_GROUP_PATH(60))
+                .parallel_bands(parallel)
+                .open()
+                .unwrap();
+            Raster::from_file(file, Indexes::all()).unwrap()
+        };
+        assert_eq!(open(true).band_names(), open(false).band_names());
+    }
+
+    #[rstest]
+    #[test_log::test]
+    fn threaded_open_matches_default() {
+        let read = |options| {
+            let raster =
+                gdal_engine::open_with::<u16>(SENTINEL2_RESOLUTION_GROUP_PATH(10), &options)
+                    .unwrap();
+            let view = raster.view(None, Indexes::from([0usize])).unwrap();
+            view.clip(ViewBounds::new((0, 0), (64, 64)))
+                .unwrap()
+                .read()
+                .unwrap()
+        };
+        let threaded = read(OpenOptions::default().num_threads(Some(2)));
+        assert_eq!(threaded.as_ref(), read(OpenOptions::default()).as_ref());
+    }
+
+    #[rstest]
+    #[test_log::test]
+    fn content_hash_is_stable() {
+        let open = |indexes| {
+            Raster::new::<GdalFile<u16>>(SENTINEL2_RESOLUTION_GROUP_PATH(10), indexes).unwrap()
+        };
+        let raster = open(Indexes::from([0usize, 1]));
+        assert_eq!(
+            raster.content_hash(),
+            open(Indexes::from([0usize, 1])).content_hash()
+        );
+        assert_ne!(
+            raster.content_hash(),
+            open(Indexes::from([1usize, 0])).content_hash()
+        );
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[rstest]
+    #[test_log::test]
+    fn into_ndarray_keeps_view_shape() {
+        let sentinel_raster =
+            gdal_engine::open::<u16>(SENTINEL2_RESOLUTION_GROUP_PATH(10)).unwrap();
+        let view = sentinel_raster
+            .view(None, Indexes::all())
+            .unwrap()
+            .clip(ViewBounds::new((0, 0), (125, 250)))
+            .unwrap();
+        let array_shape = view.array_shape();
+        let arr = view.read().unwrap().into_ndarray();
+        assert_eq!(arr.shape(), array_shape);
+    }
+
     #[rstest]
     #[test_log::test]
     fn convert_to_ndarray() {
@@ -185,43 +638,25 @@ mod tests {
     #[test_log::test]
     fn as_rgb_image() {
         use image;
-        //use ndarray::s;
-
-        let sentinel_raster = gdal_engine::open::<u16>(
-            // SENTINEL2_RESOLUTION_GROUP_PATH(10)
-            SENTINEL2_FILE_PATH(),
-        )
-        .unwrap();
 
-        let view = sentinel_raster
-            .view(None, Indexes::from([0, 4, 15]))
+        let resolution_group_bounds = GdalFile::<u16>::open(SENTINEL2_RESOLUTION_GROUP_PATH(10))
             .unwrap()
-            .clip(ViewBounds::new((500, 0), (500, 1000)))
+            .geo_bounds()
             .unwrap();
+        let origin = resolution_group_bounds.origin();
+        let clip = GeoBounds::from(CrsGeometry::new(
+            Crs::new(resolution_group_bounds.crs()),
+            Rect::new(origin, origin + Coord::from((5000., 10000.))),
+        ));
 
-        let (data, shape) = view.read().unwrap().to_owned_parts();
-        let shape = [shape[0], shape[2], shape[1]];
-        let arr = ndarray::Array3::from_shape_vec(shape, data.to_vec()).unwrap();
-        let arr_dim = arr.dim();
-        info!("as ndarray: {:?}", arr_dim);
-        //info!("as ndarray: {:?}", arr); //.slice(s![0.., ..10, (arr_dim.2 - 10)..]));
-
-        let arr = arr.permuted_axes([1, 2, 0]); // rearrange axes to (W, H, C)
-        let arr = arr.mapv(u32::from);
-        let arr_max = arr
-            .map_axis(Axis(0), |axis| *axis.iter().max().unwrap())
-            .map_axis(Axis(0), |axis| *axis.iter().max().unwrap());
-        let broadcasted_arr_max = arr_max.broadcast(arr.dim()).unwrap();
-
-        let arr = ((arr * 255) / broadcasted_arr_max).mapv(|val| val as u8);
-        info!("as ndarray: {:?}", arr.dim());
-        let _ = image::RgbImage::from_raw(
-            arr.dim().0 as u32,
-            arr.dim().1 as u32,
-            arr.into_iter().collect(),
-        )
-        .unwrap()
-        .save(format!("data/{SENTINEL2_FILE_NAME}.png"))
-        .unwrap();
+        let buff =
+            gdal_engine::Sentinel2::true_color_rgb(SENTINEL2_FILE_PATH(), Some(clip)).unwrap();
+        let (data, shape) = buff.to_channel_last().to_owned_parts();
+        let [height, width, _] = shape;
+        info!("as (H, W, C): {:?}", shape);
+        let _ = image::RgbImage::from_raw(width as u32, height as u32, data.into_vec())
+            .unwrap()
+            .save(format!("data/{SENTINEL2_FILE_NAME}.png"))
+            .unwrap();
     }
 }